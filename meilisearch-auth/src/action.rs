@@ -24,6 +24,10 @@ pub enum Action {
     IndexesDelete = actions::INDEXES_DELETE,
     #[serde(rename = "tasks.get")]
     TasksGet = actions::TASKS_GET,
+    #[serde(rename = "tasks.cancel")]
+    TasksCancel = actions::TASKS_CANCEL,
+    #[serde(rename = "tasks.delete")]
+    TasksDelete = actions::TASKS_DELETE,
     #[serde(rename = "settings.get")]
     SettingsGet = actions::SETTINGS_GET,
     #[serde(rename = "settings.update")]
@@ -52,6 +56,8 @@ impl Action {
             INDEXES_UPDATE => Some(Self::IndexesUpdate),
             INDEXES_DELETE => Some(Self::IndexesDelete),
             TASKS_GET => Some(Self::TasksGet),
+            TASKS_CANCEL => Some(Self::TasksCancel),
+            TASKS_DELETE => Some(Self::TasksDelete),
             SETTINGS_GET => Some(Self::SettingsGet),
             SETTINGS_UPDATE => Some(Self::SettingsUpdate),
             STATS_GET => Some(Self::StatsGet),
@@ -75,6 +81,8 @@ impl Action {
             Self::IndexesUpdate => INDEXES_UPDATE,
             Self::IndexesDelete => INDEXES_DELETE,
             Self::TasksGet => TASKS_GET,
+            Self::TasksCancel => TASKS_CANCEL,
+            Self::TasksDelete => TASKS_DELETE,
             Self::SettingsGet => SETTINGS_GET,
             Self::SettingsUpdate => SETTINGS_UPDATE,
             Self::StatsGet => STATS_GET,
@@ -101,4 +109,6 @@ pub mod actions {
     pub const DUMPS_CREATE: u8 = 13;
     pub const DUMPS_GET: u8 = 14;
     pub const VERSION: u8 = 15;
+    pub const TASKS_CANCEL: u8 = 16;
+    pub const TASKS_DELETE: u8 = 17;
 }
@@ -133,6 +133,14 @@ pub enum Code {
 
     Filter,
     Sort,
+    InvalidSearchCursor,
+    InvalidDocumentsCursor,
+    InvalidDocumentEdit,
+    InvalidFacetName,
+    InvalidAttributesToSearchOn,
+    InvalidSearchRankingRules,
+    InvalidSemanticRatio,
+    InvalidVectorDimensions,
 
     BadParameter,
     BadRequest,
@@ -147,6 +155,9 @@ pub enum Code {
     NoSpaceLeftOnDevice,
     DumpNotFound,
     TaskNotFound,
+    TaskNotCancelable,
+    TaskWaitTimeout,
+    TaskInterrupted,
     PayloadTooLarge,
     RetrieveDocument,
     SearchDocuments,
@@ -157,6 +168,7 @@ pub enum Code {
 
     InvalidContentType,
     MissingContentType,
+    InvalidAccept,
     MalformedPayload,
     MissingPayload,
 
@@ -213,6 +225,31 @@ impl Code {
             Filter => ErrCode::invalid("invalid_filter", StatusCode::BAD_REQUEST),
             // error related to sorts
             Sort => ErrCode::invalid("invalid_sort", StatusCode::BAD_REQUEST),
+            // error related to facet search
+            InvalidFacetName => ErrCode::invalid("invalid_facet_name", StatusCode::BAD_REQUEST),
+            // error related to the ranking overrides of a search
+            InvalidAttributesToSearchOn => {
+                ErrCode::invalid("invalid_attributes_to_search_on", StatusCode::BAD_REQUEST)
+            }
+            InvalidSearchRankingRules => {
+                ErrCode::invalid("invalid_search_ranking_rules", StatusCode::BAD_REQUEST)
+            }
+            InvalidSemanticRatio => {
+                ErrCode::invalid("invalid_semantic_ratio", StatusCode::BAD_REQUEST)
+            }
+            InvalidVectorDimensions => {
+                ErrCode::invalid("invalid_vector_dimensions", StatusCode::BAD_REQUEST)
+            }
+            InvalidDocumentEdit => {
+                ErrCode::invalid("invalid_document_edit", StatusCode::BAD_REQUEST)
+            }
+            InvalidDocumentsCursor => {
+                ErrCode::invalid("invalid_documents_cursor", StatusCode::BAD_REQUEST)
+            }
+            // error related to search cursors
+            InvalidSearchCursor => {
+                ErrCode::invalid("invalid_search_cursor", StatusCode::BAD_REQUEST)
+            }
 
             BadParameter => ErrCode::invalid("bad_parameter", StatusCode::BAD_REQUEST),
             BadRequest => ErrCode::invalid("bad_request", StatusCode::BAD_REQUEST),
@@ -228,6 +265,11 @@ impl Code {
                 ErrCode::authentication("missing_authorization_header", StatusCode::UNAUTHORIZED)
             }
             TaskNotFound => ErrCode::invalid("task_not_found", StatusCode::NOT_FOUND),
+            TaskNotCancelable => ErrCode::invalid("task_not_cancelable", StatusCode::CONFLICT),
+            TaskWaitTimeout => ErrCode::invalid("task_wait_timeout", StatusCode::GATEWAY_TIMEOUT),
+            TaskInterrupted => {
+                ErrCode::internal("task_interrupted", StatusCode::INTERNAL_SERVER_ERROR)
+            }
             DumpNotFound => ErrCode::invalid("dump_not_found", StatusCode::NOT_FOUND),
             NoSpaceLeftOnDevice => {
                 ErrCode::internal("no_space_left_on_device", StatusCode::INTERNAL_SERVER_ERROR)
@@ -255,6 +297,7 @@ impl Code {
             InvalidContentType => {
                 ErrCode::invalid("invalid_content_type", StatusCode::UNSUPPORTED_MEDIA_TYPE)
             }
+            InvalidAccept => ErrCode::invalid("invalid_accept", StatusCode::NOT_ACCEPTABLE),
             MissingPayload => ErrCode::invalid("missing_payload", StatusCode::BAD_REQUEST),
 
             // error related to keys
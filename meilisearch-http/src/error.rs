@@ -12,6 +12,21 @@ pub enum MeilisearchHttpError {
         .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", ")
     )]
     InvalidContentType(String, Vec<String>),
+    #[error(
+        "The Accept header `{0}` is invalid. Accepted values for the Accept header are: {}",
+        .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", ")
+    )]
+    InvalidAccept(String, Vec<String>),
+    #[error("Task type `{0}` is invalid. Available task types are: `indexCreation`, `indexUpdate`, `indexDeletion`, `documentAddition`, `documentPartial`, `documentDeletion`, `documentEdition`, `settingsUpdate`, `clearAll`.")]
+    InvalidTaskTypesFilter(String),
+    #[error("Task status `{0}` is invalid. Available task statuses are: `enqueued`, `processing`, `succeeded`, `failed`, `canceled`.")]
+    InvalidTaskStatusesFilter(String),
+    #[error(
+        "The `deepMerge` parameter can only be used to update documents with a `PUT` request."
+    )]
+    DeepMergeWithoutUpdate,
+    #[error("Deleting tasks requires at least one of the `type`, `status`, `indexUid`, `from`, `afterEnqueuedAt`, `beforeEnqueuedAt`, `afterFinishedAt` or `beforeFinishedAt` parameters.")]
+    MissingTasksDeletionFilter,
 }
 
 impl ErrorCode for MeilisearchHttpError {
@@ -19,6 +34,11 @@ impl ErrorCode for MeilisearchHttpError {
         match self {
             MeilisearchHttpError::MissingContentType(_) => Code::MissingContentType,
             MeilisearchHttpError::InvalidContentType(_, _) => Code::InvalidContentType,
+            MeilisearchHttpError::InvalidAccept(_, _) => Code::InvalidAccept,
+            MeilisearchHttpError::InvalidTaskTypesFilter(_)
+            | MeilisearchHttpError::InvalidTaskStatusesFilter(_)
+            | MeilisearchHttpError::DeepMergeWithoutUpdate
+            | MeilisearchHttpError::MissingTasksDeletionFilter => Code::BadRequest,
         }
     }
 }
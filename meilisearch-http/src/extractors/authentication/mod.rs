@@ -1,4 +1,4 @@
-mod error;
+pub mod error;
 
 use std::marker::PhantomData;
 use std::ops::Deref;
@@ -9,7 +9,7 @@ pub mod helpers;
 pub mod option;
 pub mod routes;
 
-use std::sync::{atomic::AtomicBool, Arc};
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::error::MeilisearchHttpError;
@@ -25,17 +25,9 @@ use extractors::payload::PayloadConfig;
 use meilisearch_auth::AuthController;
 use meilisearch_lib::MeiliSearch;
 
-pub static AUTOBATCHING_ENABLED: AtomicBool = AtomicBool::new(false);
-
 pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
     let mut meilisearch = MeiliSearch::builder();
 
-    // enable autobatching?
-    let _ = AUTOBATCHING_ENABLED.store(
-        opt.scheduler_options.enable_auto_batching,
-        std::sync::atomic::Ordering::Relaxed,
-    );
-
     meilisearch
         .set_max_index_size(opt.max_index_size.get_bytes() as usize)
         .set_max_task_store_size(opt.max_task_db_size.get_bytes() as usize)
@@ -47,7 +39,9 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
         // dump
         .set_ignore_missing_dump(opt.ignore_missing_dump)
         .set_ignore_dump_if_db_exists(opt.ignore_dump_if_db_exists)
-        .set_dump_dst(opt.dumps_dir.clone());
+        .set_dump_dst(opt.dumps_dir.clone())
+        // task webhook
+        .set_task_webhook_payload(|task| serde_json::json!(task::TaskView::from(task)));
 
     if let Some(ref path) = opt.import_snapshot {
         meilisearch.set_import_snapshot(path.clone());
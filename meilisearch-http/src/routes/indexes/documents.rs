@@ -1,5 +1,6 @@
 use actix_web::error::PayloadError;
-use actix_web::http::header::CONTENT_TYPE;
+use actix_web::guard;
+use actix_web::http::header::{ACCEPT, CONTENT_TYPE};
 use actix_web::web::Bytes;
 use actix_web::HttpMessage;
 use actix_web::{web, HttpRequest, HttpResponse};
@@ -7,8 +8,12 @@ use bstr::ByteSlice;
 use futures::{Stream, StreamExt};
 use log::debug;
 use meilisearch_error::ResponseError;
+use meilisearch_lib::document_formats::{write_csv, write_csv_header, write_ndjson};
+use meilisearch_lib::index;
 use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
 use meilisearch_lib::milli::update::IndexDocumentsMethod;
+use meilisearch_lib::milli::update::Setting;
+use meilisearch_lib::tasks::task::{Task, TaskPriority};
 use meilisearch_lib::MeiliSearch;
 use mime::Mime;
 use once_cell::sync::Lazy;
@@ -21,11 +26,17 @@ use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::payload::Payload;
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::task::SummarizedTaskView;
+use crate::routes::{task_response, TaskOptionsQuery};
 
 const DEFAULT_RETRIEVE_DOCUMENTS_OFFSET: usize = 0;
 const DEFAULT_RETRIEVE_DOCUMENTS_LIMIT: usize = 20;
 
+/// The number of documents read from the index for each chunk of an export.
+const EXPORT_CHUNK_SIZE: usize = 1000;
+
+static ACCEPTED_EXPORT_TYPE: Lazy<Vec<String>> =
+    Lazy::new(|| vec!["application/x-ndjson".to_string(), "text/csv".to_string()]);
+
 static ACCEPTED_CONTENT_TYPE: Lazy<Vec<String>> = Lazy::new(|| {
     vec![
         "application/json".to_string(),
@@ -63,6 +74,34 @@ fn extract_mime_type(req: &HttpRequest) -> Result<Option<Mime>, MeilisearchHttpE
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Ndjson,
+    Csv,
+}
+
+/// Returns the export format preferred by the Accept header, taking the media types in order and
+/// ignoring their quality. NDJSON is the default.
+fn extract_export_format(req: &HttpRequest) -> Result<ExportFormat, MeilisearchHttpError> {
+    let accept = match req.headers().get(ACCEPT) {
+        Some(accept) => accept.to_str().unwrap_or_default(),
+        None => return Ok(ExportFormat::Ndjson),
+    };
+
+    for media_type in accept.split(',') {
+        match media_type.split(';').next().unwrap_or_default().trim() {
+            "application/x-ndjson" | "application/*" | "*/*" => return Ok(ExportFormat::Ndjson),
+            "text/csv" | "text/*" => return Ok(ExportFormat::Csv),
+            _ => (),
+        }
+    }
+
+    Err(MeilisearchHttpError::InvalidAccept(
+        accept.to_string(),
+        ACCEPTED_EXPORT_TYPE.clone(),
+    ))
+}
+
 #[derive(Deserialize)]
 pub struct DocumentParam {
     index_uid: String,
@@ -77,8 +116,28 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .route(web::put().to(SeqHandler(update_documents)))
             .route(web::delete().to(SeqHandler(clear_all_documents))),
     )
-    // this route needs to be before the /documents/{document_id} to match properly
-    .service(web::resource("/delete-batch").route(web::post().to(SeqHandler(delete_documents))))
+    // these routes need to be before the /documents/{document_id} to match properly, they are
+    // guarded on their method so that the documents with the same id can still be fetched and deleted
+    .service(
+        web::resource("/delete-batch")
+            .guard(guard::Post())
+            .route(web::post().to(SeqHandler(delete_documents))),
+    )
+    .service(
+        web::resource("/delete")
+            .guard(guard::Post())
+            .route(web::post().to(SeqHandler(delete_documents_by_filter))),
+    )
+    .service(
+        web::resource("/fetch")
+            .guard(guard::Post())
+            .route(web::post().to(SeqHandler(browse_documents))),
+    )
+    .service(
+        web::resource("/edit")
+            .guard(guard::Post())
+            .route(web::post().to(SeqHandler(edit_documents))),
+    )
     .service(
         web::resource("/{document_id}")
             .route(web::get().to(SeqHandler(get_document)))
@@ -102,15 +161,17 @@ pub async fn get_document(
 pub async fn delete_document(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<DocumentParam>,
+    params: web::Query<TaskOptionsQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let DocumentParam {
         document_id,
         index_uid,
     } = path.into_inner();
     let update = Update::DeleteDocuments(vec![document_id]);
-    let task: SummarizedTaskView = meilisearch.register_update(index_uid, update).await?.into();
-    debug!("returns: {:?}", task);
-    Ok(HttpResponse::Accepted().json(task))
+    let task = meilisearch
+        .register_update(index_uid, update, params.priority)
+        .await?;
+    task_response(&meilisearch, task, params.wait_for_task).await
 }
 
 #[derive(Deserialize, Debug)]
@@ -144,16 +205,114 @@ pub async fn get_all_documents(
             params.offset.unwrap_or(DEFAULT_RETRIEVE_DOCUMENTS_OFFSET),
             params.limit.unwrap_or(DEFAULT_RETRIEVE_DOCUMENTS_LIMIT),
             attributes_to_retrieve,
+            false,
         )
         .await?;
     debug!("returns: {:?}", documents);
     Ok(HttpResponse::Ok().json(documents))
 }
 
+pub async fn browse_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<index::BrowseQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let result = meilisearch
+        .browse_documents(path.into_inner(), body.into_inner())
+        .await?;
+    debug!("returns: {:?}", result);
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Streams all the displayed documents of the index as NDJSON or CSV. The documents are read by
+/// chunks, so a document indexed during the export may or may not be part of it. The vectors are
+/// exported under `_vectors` in NDJSON only, a CSV can't hold them.
+pub async fn export_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ResponseError> {
+    let index_uid = path.into_inner();
+    let format = extract_export_format(&req)?;
+
+    // The CSV columns must be known before the first document is written.
+    let fields = match format {
+        ExportFormat::Csv => {
+            match meilisearch
+                .settings(index_uid.clone())
+                .await?
+                .displayed_attributes
+            {
+                Setting::Set(fields) => fields,
+                _ => meilisearch
+                    .get_index_stats(index_uid.clone())
+                    .await?
+                    .field_distribution
+                    .into_keys()
+                    .collect(),
+            }
+        }
+        ExportFormat::Ndjson => {
+            // Fails before streaming anything when the index doesn't exist.
+            meilisearch.get_index(index_uid.clone()).await?;
+            Vec::new()
+        }
+    };
+
+    let content_type = match format {
+        ExportFormat::Ndjson => "application/x-ndjson",
+        ExportFormat::Csv => "text/csv",
+    };
+
+    let meilisearch = (*meilisearch).clone();
+    let stream = async_stream::try_stream! {
+        if format == ExportFormat::Csv {
+            let mut header = Vec::new();
+            write_csv_header(&fields, &mut header)?;
+            yield Bytes::from(header);
+        }
+
+        let mut offset = 0;
+        loop {
+            let chunk = meilisearch
+                .documents(
+                    index_uid.clone(),
+                    offset,
+                    EXPORT_CHUNK_SIZE,
+                    None,
+                    format == ExportFormat::Ndjson,
+                )
+                .await?;
+
+            let mut buffer = Vec::new();
+            match format {
+                ExportFormat::Ndjson => write_ndjson(&chunk, &mut buffer)?,
+                ExportFormat::Csv => write_csv(&fields, &chunk, &mut buffer)?,
+            }
+            yield Bytes::from(buffer);
+
+            if chunk.len() < EXPORT_CHUNK_SIZE {
+                break;
+            }
+            offset += EXPORT_CHUNK_SIZE;
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .streaming::<_, ResponseError>(stream))
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct UpdateDocumentsQuery {
     pub primary_key: Option<String>,
+    pub wait_for_task: Option<u64>,
+    pub priority: Option<TaskPriority>,
+    /// Deep merges the nested objects of the documents, see `Index::merge_documents`.
+    #[serde(default)]
+    pub deep_merge: bool,
 }
 
 pub async fn add_documents(
@@ -168,6 +327,10 @@ pub async fn add_documents(
     let params = params.into_inner();
     let index_uid = path.into_inner();
 
+    if params.deep_merge {
+        return Err(MeilisearchHttpError::DeepMergeWithoutUpdate.into());
+    }
+
     analytics.add_documents(
         &params,
         meilisearch.get_index(index_uid.clone()).await.is_err(),
@@ -177,16 +340,16 @@ pub async fn add_documents(
     let allow_index_creation = meilisearch.filters().allow_index_creation;
     let task = document_addition(
         extract_mime_type(&req)?,
-        meilisearch,
+        &meilisearch,
         index_uid,
-        params.primary_key,
+        &params,
         body,
         IndexDocumentsMethod::ReplaceDocuments,
         allow_index_creation,
     )
     .await?;
 
-    Ok(HttpResponse::Accepted().json(task))
+    task_response(&meilisearch, task, params.wait_for_task).await
 }
 
 pub async fn update_documents(
@@ -198,6 +361,7 @@ pub async fn update_documents(
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", params);
+    let params = params.into_inner();
     let index_uid = path.into_inner();
 
     analytics.update_documents(
@@ -209,27 +373,27 @@ pub async fn update_documents(
     let allow_index_creation = meilisearch.filters().allow_index_creation;
     let task = document_addition(
         extract_mime_type(&req)?,
-        meilisearch,
+        &meilisearch,
         index_uid,
-        params.into_inner().primary_key,
+        &params,
         body,
         IndexDocumentsMethod::UpdateDocuments,
         allow_index_creation,
     )
     .await?;
 
-    Ok(HttpResponse::Accepted().json(task))
+    task_response(&meilisearch, task, params.wait_for_task).await
 }
 
 async fn document_addition(
     mime_type: Option<Mime>,
-    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    meilisearch: &MeiliSearch,
     index_uid: String,
-    primary_key: Option<String>,
+    params: &UpdateDocumentsQuery,
     body: Payload,
     method: IndexDocumentsMethod,
     allow_index_creation: bool,
-) -> Result<SummarizedTaskView, ResponseError> {
+) -> Result<Task, ResponseError> {
     let format = match mime_type
         .as_ref()
         .map(|m| (m.type_().as_str(), m.subtype().as_str()))
@@ -253,21 +417,24 @@ async fn document_addition(
 
     let update = Update::DocumentAddition {
         payload: Box::new(payload_to_stream(body)),
-        primary_key,
+        primary_key: params.primary_key.clone(),
         method,
         format,
         allow_index_creation,
+        deep_merge: params.deep_merge,
     };
 
-    let task = meilisearch.register_update(index_uid, update).await?.into();
+    let task = meilisearch
+        .register_update(index_uid, update, params.priority)
+        .await?;
 
-    debug!("returns: {:?}", task);
     Ok(task)
 }
 
 pub async fn delete_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
+    params: web::Query<TaskOptionsQuery>,
     body: web::Json<Vec<Value>>,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", body);
@@ -281,25 +448,66 @@ pub async fn delete_documents(
         .collect();
 
     let update = Update::DeleteDocuments(ids);
-    let task: SummarizedTaskView = meilisearch
-        .register_update(path.into_inner(), update)
-        .await?
-        .into();
+    let task = meilisearch
+        .register_update(path.into_inner(), update, params.priority)
+        .await?;
+
+    task_response(&meilisearch, task, params.wait_for_task).await
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentDeletionByFilter {
+    filter: Value,
+}
 
-    debug!("returns: {:?}", task);
-    Ok(HttpResponse::Accepted().json(task))
+pub async fn delete_documents_by_filter(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<TaskOptionsQuery>,
+    body: web::Json<DocumentDeletionByFilter>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let update = Update::DeleteDocumentsByFilter(body.into_inner().filter);
+    let task = meilisearch
+        .register_update(path.into_inner(), update, params.priority)
+        .await?;
+
+    task_response(&meilisearch, task, params.wait_for_task).await
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentEdition {
+    filter: Option<Value>,
+    edits: Vec<index::DocumentEdit>,
+}
+
+pub async fn edit_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<TaskOptionsQuery>,
+    body: web::Json<DocumentEdition>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let DocumentEdition { filter, edits } = body.into_inner();
+    let update = Update::EditDocuments { filter, edits };
+    let task = meilisearch
+        .register_update(path.into_inner(), update, params.priority)
+        .await?;
+
+    task_response(&meilisearch, task, params.wait_for_task).await
 }
 
 pub async fn clear_all_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
+    params: web::Query<TaskOptionsQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let update = Update::ClearDocuments;
-    let task: SummarizedTaskView = meilisearch
-        .register_update(path.into_inner(), update)
-        .await?
-        .into();
+    let task = meilisearch
+        .register_update(path.into_inner(), update, params.priority)
+        .await?;
 
-    debug!("returns: {:?}", task);
-    Ok(HttpResponse::Accepted().json(task))
+    task_response(&meilisearch, task, params.wait_for_task).await
 }
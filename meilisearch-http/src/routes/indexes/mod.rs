@@ -13,6 +13,7 @@ use crate::extractors::sequential_extractor::SeqHandler;
 use crate::task::SummarizedTaskView;
 
 pub mod documents;
+pub mod facet_search;
 pub mod search;
 pub mod settings;
 pub mod tasks;
@@ -32,8 +33,13 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                     .route(web::delete().to(SeqHandler(delete_index))),
             )
             .service(web::resource("/stats").route(web::get().to(SeqHandler(get_index_stats))))
+            .service(
+                web::resource("/export")
+                    .route(web::get().to(SeqHandler(documents::export_documents))),
+            )
             .service(web::scope("/documents").configure(documents::configure))
             .service(web::scope("/search").configure(search::configure))
+            .service(web::scope("/facet-search").configure(facet_search::configure))
             .service(web::scope("/tasks").configure(tasks::configure))
             .service(web::scope("/settings").configure(settings::configure)),
     );
@@ -78,7 +84,7 @@ pub async fn create_index(
     );
 
     let update = Update::CreateIndex { primary_key };
-    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch.register_update(uid, update, None).await?.into();
 
     Ok(HttpResponse::Accepted().json(task))
 }
@@ -133,7 +139,7 @@ pub async fn update_index(
     };
 
     let task: SummarizedTaskView = meilisearch
-        .register_update(path.into_inner(), update)
+        .register_update(path.into_inner(), update, None)
         .await?
         .into();
 
@@ -147,7 +153,7 @@ pub async fn delete_index(
 ) -> Result<HttpResponse, ResponseError> {
     let uid = path.into_inner();
     let update = Update::DeleteIndex;
-    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch.register_update(uid, update, None).await?.into();
 
     Ok(HttpResponse::Accepted().json(task))
 }
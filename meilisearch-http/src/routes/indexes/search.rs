@@ -4,7 +4,8 @@ use meilisearch_auth::IndexSearchRules;
 use meilisearch_error::ResponseError;
 use meilisearch_lib::index::{
     default_crop_length, default_crop_marker, default_highlight_post_tag,
-    default_highlight_pre_tag, SearchQuery, DEFAULT_SEARCH_LIMIT,
+    default_highlight_pre_tag, MatchingStrategy, SearchQuery, DEFAULT_SEARCH_LIMIT,
+    DEFAULT_SEMANTIC_RATIO,
 };
 use meilisearch_lib::MeiliSearch;
 use serde::Deserialize;
@@ -44,6 +45,17 @@ pub struct SearchQueryGet {
     highlight_post_tag: String,
     #[serde(default = "default_crop_marker")]
     crop_marker: String,
+    cursor: Option<String>,
+    #[serde(default = "Default::default")]
+    exhaustive_counts: bool,
+    attributes_to_search_on: Option<String>,
+    ranking_rules: Option<String>,
+    #[serde(default = "Default::default")]
+    show_ranking_score: bool,
+    #[serde(default = "Default::default")]
+    show_ranking_score_details: bool,
+    #[serde(default = "Default::default")]
+    matching_strategy: MatchingStrategy,
 }
 
 impl From<SearchQueryGet> for SearchQuery {
@@ -72,6 +84,14 @@ impl From<SearchQueryGet> for SearchQuery {
             None => None,
         };
 
+        let attributes_to_search_on = other
+            .attributes_to_search_on
+            .map(|attrs| attrs.split(',').map(String::from).collect());
+
+        let ranking_rules = other
+            .ranking_rules
+            .map(|rules| rules.split(',').map(String::from).collect());
+
         let sort = other.sort.map(|attr| fix_sort_query_parameters(&attr));
 
         Self {
@@ -89,13 +109,27 @@ impl From<SearchQueryGet> for SearchQuery {
             highlight_pre_tag: other.highlight_pre_tag,
             highlight_post_tag: other.highlight_post_tag,
             crop_marker: other.crop_marker,
+            cursor: other.cursor,
+            exhaustive_counts: other.exhaustive_counts,
+            attributes_to_search_on,
+            ranking_rules,
+            show_ranking_score: other.show_ranking_score,
+            show_ranking_score_details: other.show_ranking_score_details,
+            matching_strategy: other.matching_strategy,
+            vector: None,
+            semantic_ratio: DEFAULT_SEMANTIC_RATIO,
         }
     }
 }
 
 /// Incorporate search rules in search query
-fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
-    query.filter = match (query.filter.take(), rules.filter) {
+pub fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
+    add_search_rules_to_filter(&mut query.filter, rules);
+}
+
+/// Incorporate the filter of the search rules in a query filter
+pub fn add_search_rules_to_filter(filter: &mut Option<Value>, rules: IndexSearchRules) {
+    *filter = match (filter.take(), rules.filter) {
         (None, rules_filter) => rules_filter,
         (filter, None) => filter,
         (Some(filter), Some(rules_filter)) => {
@@ -169,10 +203,6 @@ pub async fn search_with_url_query(
 
     let search_result = search_result?;
 
-    // Tests that the nb_hits is always set to false
-    #[cfg(test)]
-    assert!(!search_result.exhaustive_nb_hits);
-
     debug!("returns: {:?}", search_result);
     Ok(HttpResponse::Ok().json(search_result))
 }
@@ -207,10 +237,6 @@ pub async fn search_with_post(
 
     let search_result = search_result?;
 
-    // Tests that the nb_hits is always set to false
-    #[cfg(test)]
-    assert!(!search_result.exhaustive_nb_hits);
-
     debug!("returns: {:?}", search_result);
     Ok(HttpResponse::Ok().json(search_result))
 }
@@ -9,7 +9,7 @@ use serde_json::json;
 
 use crate::analytics::Analytics;
 use crate::extractors::authentication::{policies::*, GuardedData};
-use crate::task::SummarizedTaskView;
+use crate::routes::{task_response, TaskOptionsQuery};
 
 #[macro_export]
 macro_rules! make_setting_route {
@@ -25,11 +25,12 @@ macro_rules! make_setting_route {
             use $crate::analytics::Analytics;
             use $crate::extractors::authentication::{policies::*, GuardedData};
             use $crate::extractors::sequential_extractor::SeqHandler;
-            use $crate::task::SummarizedTaskView;
+            use $crate::routes::{task_response, TaskOptionsQuery};
 
             pub async fn delete(
                 meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
                 index_uid: web::Path<String>,
+                params: web::Query<TaskOptionsQuery>,
             ) -> Result<HttpResponse, ResponseError> {
                 let settings = Settings {
                     $attr: Setting::Reset,
@@ -42,18 +43,17 @@ macro_rules! make_setting_route {
                     is_deletion: true,
                     allow_index_creation,
                 };
-                let task: SummarizedTaskView = meilisearch
-                    .register_update(index_uid.into_inner(), update)
-                    .await?
-                    .into();
+                let task = meilisearch
+                    .register_update(index_uid.into_inner(), update, params.priority)
+                    .await?;
 
-                debug!("returns: {:?}", task);
-                Ok(HttpResponse::Accepted().json(task))
+                task_response(&meilisearch, task, params.wait_for_task).await
             }
 
             pub async fn update(
                 meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
                 index_uid: actix_web::web::Path<String>,
+                params: web::Query<TaskOptionsQuery>,
                 body: actix_web::web::Json<Option<$type>>,
                 req: HttpRequest,
                 $analytics_var: web::Data<dyn Analytics>,
@@ -76,13 +76,11 @@ macro_rules! make_setting_route {
                     is_deletion: false,
                     allow_index_creation,
                 };
-                let task: SummarizedTaskView = meilisearch
-                    .register_update(index_uid.into_inner(), update)
-                    .await?
-                    .into();
+                let task = meilisearch
+                    .register_update(index_uid.into_inner(), update, params.priority)
+                    .await?;
 
-                debug!("returns: {:?}", task);
-                Ok(HttpResponse::Accepted().json(task))
+                task_response(&meilisearch, task, params.wait_for_task).await
             }
 
             pub async fn get(
@@ -294,6 +292,7 @@ generate_configure!(
 pub async fn update_all(
     meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
+    params: web::Query<TaskOptionsQuery>,
     body: web::Json<Settings<Unchecked>>,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
@@ -358,13 +357,11 @@ pub async fn update_all(
         is_deletion: false,
         allow_index_creation,
     };
-    let task: SummarizedTaskView = meilisearch
-        .register_update(index_uid.into_inner(), update)
-        .await?
-        .into();
+    let task = meilisearch
+        .register_update(index_uid.into_inner(), update, params.priority)
+        .await?;
 
-    debug!("returns: {:?}", task);
-    Ok(HttpResponse::Accepted().json(task))
+    task_response(&meilisearch, task, params.wait_for_task).await
 }
 
 pub async fn get_all(
@@ -379,6 +376,7 @@ pub async fn get_all(
 pub async fn delete_all(
     data: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
+    params: web::Query<TaskOptionsQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let settings = Settings::cleared().into_unchecked();
 
@@ -388,11 +386,9 @@ pub async fn delete_all(
         is_deletion: true,
         allow_index_creation,
     };
-    let task: SummarizedTaskView = data
-        .register_update(index_uid.into_inner(), update)
-        .await?
-        .into();
+    let task = data
+        .register_update(index_uid.into_inner(), update, params.priority)
+        .await?;
 
-    debug!("returns: {:?}", task);
-    Ok(HttpResponse::Accepted().json(task))
+    task_response(&data, task, params.wait_for_task).await
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use actix_web::{web, HttpResponse};
 use log::debug;
 use serde::{Deserialize, Serialize};
@@ -5,13 +7,16 @@ use time::OffsetDateTime;
 
 use meilisearch_error::ResponseError;
 use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::tasks::task::{Task, TaskPriority};
 use meilisearch_lib::MeiliSearch;
 
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::task::{SummarizedTaskView, TaskView};
 
 mod api_key;
 mod dump;
 pub mod indexes;
+mod multi_search;
 mod tasks;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -21,9 +26,47 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .service(web::scope("/dumps").configure(dump::configure))
         .service(web::resource("/stats").route(web::get().to(get_stats)))
         .service(web::resource("/version").route(web::get().to(get_version)))
+        .service(web::scope("/multi-search").configure(multi_search::configure))
         .service(web::scope("/indexes").configure(indexes::configure));
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TaskOptionsQuery {
+    /// The maximum time to wait for the registered task to finish, in milliseconds.
+    pub wait_for_task: Option<u64>,
+    /// The priority of the registered task.
+    pub priority: Option<TaskPriority>,
+}
+
+/// Builds the response of a route that registered `task`.
+///
+/// If `wait_for_task` is set, waits at most that many milliseconds for the task to finish and
+/// returns the finished task. Otherwise, the summarized task is returned right away.
+pub async fn task_response(
+    meilisearch: &MeiliSearch,
+    task: Task,
+    wait_for_task: Option<u64>,
+) -> Result<HttpResponse, ResponseError> {
+    match wait_for_task {
+        Some(timeout) => {
+            let task: TaskView = meilisearch
+                .wait_task(task.id, Duration::from_millis(timeout))
+                .await?
+                .into();
+
+            debug!("returns: {:?}", task);
+            Ok(HttpResponse::Ok().json(task))
+        }
+        None => {
+            let task: SummarizedTaskView = task.into();
+
+            debug!("returns: {:?}", task);
+            Ok(HttpResponse::Accepted().json(task))
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
 #[serde(tag = "name")]
@@ -0,0 +1,129 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::future::try_join_all;
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index::{SearchHit, SearchQuery, SearchResult};
+use meilisearch_lib::MeiliSearch;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::error::AuthenticationError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::add_search_rules;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(multi_search_with_post))));
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MultiSearchQuery {
+    queries: Vec<IndexSearchQuery>,
+    /// Whether the hits of all the queries are also returned in a single list.
+    #[serde(default)]
+    merge: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IndexSearchQuery {
+    index_uid: String,
+    query: SearchQuery,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexSearchResult {
+    index_uid: String,
+    #[serde(flatten)]
+    result: SearchResult,
+}
+
+#[derive(Debug, Serialize)]
+struct MergedSearchHit {
+    #[serde(flatten)]
+    hit: SearchHit,
+    #[serde(rename = "_indexUid")]
+    index_uid: String,
+}
+
+#[derive(Debug, Serialize)]
+struct MultiSearchResult {
+    results: Vec<IndexSearchResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hits: Option<Vec<MergedSearchHit>>,
+}
+
+pub async fn multi_search_with_post(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    params: web::Json<MultiSearchQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let MultiSearchQuery { queries, merge } = params.into_inner();
+    debug!("multi search called with params: {:?}", queries);
+
+    analytics.publish(
+        "Multi Search".to_string(),
+        json!({ "queries_count": queries.len(), "merge": merge }),
+        Some(&req),
+    );
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let meilisearch = &meilisearch;
+    let mut searches = Vec::with_capacity(queries.len());
+    for IndexSearchQuery {
+        index_uid,
+        mut query,
+    } in queries
+    {
+        // The route is not bound to an index, so the access to each index is checked here.
+        if !search_rules.is_index_authorized(&index_uid) {
+            return Err(AuthenticationError::InvalidToken.into());
+        }
+
+        // Tenant token search_rules.
+        if let Some(rules) = search_rules.get_index_search_rules(&index_uid) {
+            add_search_rules(&mut query, rules);
+        }
+
+        searches.push(async move {
+            let result = meilisearch.search(index_uid.clone(), query).await?;
+            Ok::<_, ResponseError>(IndexSearchResult { index_uid, result })
+        });
+    }
+
+    let results = try_join_all(searches).await?;
+    let hits = merge.then(|| merge_hits(&results));
+    let search_result = MultiSearchResult { results, hits };
+
+    debug!("returns: {:?}", search_result);
+    Ok(HttpResponse::Ok().json(search_result))
+}
+
+/// Merges the hits of all the results into a single list. The hits are ranked by their position in
+/// the results of their own query, and the hits at the same position are ordered like the queries.
+fn merge_hits(results: &[IndexSearchResult]) -> Vec<MergedSearchHit> {
+    let longest = results
+        .iter()
+        .map(|index_result| index_result.result.hits.len())
+        .max()
+        .unwrap_or_default();
+
+    (0..longest)
+        .flat_map(|position| {
+            results.iter().filter_map(move |index_result| {
+                index_result
+                    .result
+                    .hits
+                    .get(position)
+                    .map(|hit| MergedSearchHit {
+                        hit: hit.clone(),
+                        index_uid: index_result.index_uid.clone(),
+                    })
+            })
+        })
+        .collect()
+}
@@ -1,28 +1,345 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use actix_web::http::header::CACHE_CONTROL;
 use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use log::debug;
+use meilisearch_auth::SearchRules;
 use meilisearch_error::ResponseError;
-use meilisearch_lib::tasks::task::TaskId;
+use meilisearch_lib::tasks::task::{Task, TaskId};
 use meilisearch_lib::tasks::TaskFilter;
 use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
 use serde_json::json;
+use time::OffsetDateTime;
+use tokio::sync::broadcast::error::RecvError;
 
 use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::task::{TaskListView, TaskView};
+use crate::task::{PaginatedTaskListView, TaskStatus, TaskType, TaskView};
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("").route(web::get().to(SeqHandler(get_tasks))))
-        .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))));
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(get_tasks)))
+            .route(web::delete().to(SeqHandler(delete_tasks))),
+    )
+    .service(web::resource("/stream").route(web::get().to(SeqHandler(stream_tasks))))
+    .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))))
+    .service(web::resource("/{task_id}/cancel").route(web::post().to(SeqHandler(cancel_task))));
+}
+
+const DEFAULT_LIST_TASKS_LIMIT: usize = 20;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TasksFilterQuery {
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    status: Option<String>,
+    index_uid: Option<String>,
+    limit: Option<usize>,
+    from: Option<TaskId>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_finished_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_finished_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TasksDeletionQuery {
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    status: Option<String>,
+    index_uid: Option<String>,
+    from: Option<TaskId>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_finished_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_finished_at: Option<OffsetDateTime>,
+}
+
+impl From<TasksDeletionQuery> for TasksFilterQuery {
+    fn from(other: TasksDeletionQuery) -> Self {
+        Self {
+            type_: other.type_,
+            status: other.status,
+            index_uid: other.index_uid,
+            limit: None,
+            from: other.from,
+            after_enqueued_at: other.after_enqueued_at,
+            before_enqueued_at: other.before_enqueued_at,
+            after_finished_at: other.after_finished_at,
+            before_finished_at: other.before_finished_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TasksStreamQuery {
+    index_uid: Option<String>,
+}
+
+/// Parses a comma-separated list of values.
+fn parse_comma_separated<T: FromStr>(values: &str) -> Result<Vec<T>, T::Err> {
+    values
+        .split(',')
+        .map(|value| value.trim().parse())
+        .collect()
+}
+
+/// Returns whether `date` is in the range delimited by the optional `after` and `before` bounds.
+fn in_date_range(
+    date: Option<OffsetDateTime>,
+    after: Option<OffsetDateTime>,
+    before: Option<OffsetDateTime>,
+) -> bool {
+    if after.is_none() && before.is_none() {
+        return true;
+    }
+
+    date.map_or(false, |date| {
+        after.map_or(true, |after| date > after) && before.map_or(true, |before| date < before)
+    })
+}
+
+/// Builds the filter described by `params`, restricted to the indexes allowed by `search_rules`.
+/// Returns `None` if none of the requested indexes can be seen with these rules.
+fn tasks_filter(
+    params: TasksFilterQuery,
+    search_rules: &SearchRules,
+) -> Result<Option<TaskFilter>, ResponseError> {
+    let TasksFilterQuery {
+        type_,
+        status,
+        index_uid,
+        after_enqueued_at,
+        before_enqueued_at,
+        after_finished_at,
+        before_finished_at,
+        ..
+    } = params;
+
+    let types = type_
+        .as_deref()
+        .map(parse_comma_separated::<TaskType>)
+        .transpose()?;
+    let statuses = status
+        .as_deref()
+        .map(parse_comma_separated::<TaskStatus>)
+        .transpose()?;
+
+    let mut filters = TaskFilter::default();
+    match index_uid {
+        Some(index_uids) => {
+            let index_uids: Vec<_> = index_uids
+                .split(',')
+                .map(str::trim)
+                .filter(|index_uid| search_rules.is_index_authorized(index_uid))
+                .map(String::from)
+                .collect();
+
+            if index_uids.is_empty() {
+                return Ok(None);
+            }
+
+            for index_uid in index_uids {
+                filters.filter_index(index_uid);
+            }
+        }
+        None if !search_rules.is_index_authorized("*") => {
+            for (index, _policy) in search_rules.clone() {
+                filters.filter_index(index);
+            }
+        }
+        None => (),
+    }
+
+    filters.filter_fn(move |task| {
+        types
+            .as_ref()
+            .map_or(true, |types| types.contains(&TaskType::from(&task.content)))
+            && statuses.as_ref().map_or(true, |statuses| {
+                task.events
+                    .last()
+                    .map_or(false, |event| statuses.contains(&TaskStatus::from(event)))
+            })
+            && in_date_range(task.enqueued_at(), after_enqueued_at, before_enqueued_at)
+            && in_date_range(task.finished_at(), after_finished_at, before_finished_at)
+    });
+
+    Ok(Some(filters))
 }
 
 async fn get_tasks(
     meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    params: web::Query<TasksFilterQuery>,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
+    let params = params.into_inner();
+
     analytics.publish(
         "Tasks Seen".to_string(),
-        json!({ "per_task_uid": false }),
+        json!({
+            "per_task_uid": false,
+            "filtered_by_index_uid": params.index_uid.is_some(),
+            "filtered_by_type": params.type_.is_some(),
+            "filtered_by_status": params.status.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIST_TASKS_LIMIT);
+    let from = params.from;
+
+    let filters = match tasks_filter(params, &meilisearch.filters().search_rules)? {
+        Some(filters) => filters,
+        None => {
+            let tasks = PaginatedTaskListView::new(Vec::new(), limit, None, None);
+            return Ok(HttpResponse::Ok().json(tasks));
+        }
+    };
+
+    // We fetch one more task to know if there is a next page.
+    let mut tasks = meilisearch
+        .list_tasks(Some(filters), Some(limit.saturating_add(1)), from)
+        .await?;
+
+    let next = if tasks.len() > limit {
+        tasks.pop().map(|task| task.id)
+    } else {
+        None
+    };
+    let from = tasks.first().map(|task| task.id);
+
+    let tasks = PaginatedTaskListView::new(
+        tasks.into_iter().map(TaskView::from).collect(),
+        limit,
+        from,
+        next,
+    );
+
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+async fn delete_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_DELETE }>, MeiliSearch>,
+    params: web::Query<TasksDeletionQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let params = TasksFilterQuery::from(params.into_inner());
+
+    // Deleting every task at once is most likely a mistake, the filters must be explicit.
+    if params.type_.is_none()
+        && params.status.is_none()
+        && params.index_uid.is_none()
+        && params.from.is_none()
+        && params.after_enqueued_at.is_none()
+        && params.before_enqueued_at.is_none()
+        && params.after_finished_at.is_none()
+        && params.before_finished_at.is_none()
+    {
+        return Err(MeilisearchHttpError::MissingTasksDeletionFilter.into());
+    }
+
+    analytics.publish(
+        "Tasks Deleted".to_string(),
+        json!({
+            "filtered_by_index_uid": params.index_uid.is_some(),
+            "filtered_by_type": params.type_.is_some(),
+            "filtered_by_status": params.status.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let from = params.from;
+    let deleted_tasks = match tasks_filter(params, &meilisearch.filters().search_rules)? {
+        Some(filters) => meilisearch.delete_tasks(Some(filters), from).await?,
+        None => 0,
+    };
+
+    let response = json!({ "deletedTasks": deleted_tasks });
+
+    debug!("returns: {:?}", response);
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Streams the tasks as server-sent events, each time a new event is pushed to one of them.
+async fn stream_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    params: web::Query<TasksStreamQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let index_uids: Option<HashSet<String>> = params.into_inner().index_uid.map(|index_uids| {
+        index_uids
+            .split(',')
+            .map(|uid| uid.trim().to_string())
+            .collect()
+    });
+
+    analytics.publish(
+        "Tasks Streamed".to_string(),
+        json!({ "filtered_by_index_uid": index_uids.is_some() }),
+        Some(&req),
+    );
+
+    let search_rules = meilisearch.filters().search_rules.clone();
+    let is_visible = move |task: &Task| {
+        search_rules.is_index_authorized(&task.index_uid)
+            && index_uids
+                .as_ref()
+                .map_or(true, |uids| uids.contains(&*task.index_uid))
+    };
+
+    let mut receiver = meilisearch.subscribe_to_tasks().await;
+    let stream = async_stream::try_stream! {
+        loop {
+            match receiver.recv().await {
+                Ok(task) if is_visible(&task) => {
+                    let task = serde_json::to_string(&TaskView::from(task))?;
+                    yield Bytes::from(format!("data: {}\n\n", task));
+                }
+                Ok(_) => (),
+                // Let the client know that it missed some updates, and should fetch the tasks again.
+                Err(RecvError::Lagged(skipped)) => {
+                    yield Bytes::from(format!("event: lagged\ndata: {}\n\n", skipped));
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .streaming::<_, serde_json::Error>(stream))
+}
+
+async fn get_task(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    task_id: web::Path<TaskId>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Tasks Seen".to_string(),
+        json!({ "per_task_uid": true }),
         Some(&req),
     );
 
@@ -37,28 +354,21 @@ async fn get_tasks(
         Some(filters)
     };
 
-    let tasks: TaskListView = meilisearch
-        .list_tasks(filters, None, None)
+    let task: TaskView = meilisearch
+        .get_task(task_id.into_inner(), filters)
         .await?
-        .into_iter()
-        .map(TaskView::from)
-        .collect::<Vec<_>>()
         .into();
 
-    Ok(HttpResponse::Ok().json(tasks))
+    Ok(HttpResponse::Ok().json(task))
 }
 
-async fn get_task(
-    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+async fn cancel_task(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_CANCEL }>, MeiliSearch>,
     task_id: web::Path<TaskId>,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
-    analytics.publish(
-        "Tasks Seen".to_string(),
-        json!({ "per_task_uid": true }),
-        Some(&req),
-    );
+    analytics.publish("Task Canceled".to_string(), json!({}), Some(&req));
 
     let search_rules = &meilisearch.filters().search_rules;
     let filters = if search_rules.is_index_authorized("*") {
@@ -72,9 +382,10 @@ async fn get_task(
     };
 
     let task: TaskView = meilisearch
-        .get_task(task_id.into_inner(), filters)
+        .cancel_task(task_id.into_inner(), filters)
         .await?
         .into();
 
+    debug!("returns: {:?}", task);
     Ok(HttpResponse::Ok().json(task))
 }
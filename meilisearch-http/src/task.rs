@@ -1,33 +1,36 @@
 use std::fmt::Write;
+use std::str::FromStr;
 use std::write;
 
 use meilisearch_error::ResponseError;
-use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::index::{DocumentEdit, Settings, Unchecked};
 use meilisearch_lib::milli::update::IndexDocumentsMethod;
 use meilisearch_lib::tasks::batch::BatchId;
 use meilisearch_lib::tasks::task::{
-    DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult,
+    DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskPriority, TaskResult,
 };
 use serde::{Serialize, Serializer};
+use serde_json::Value;
 use time::{Duration, OffsetDateTime};
 
-use crate::AUTOBATCHING_ENABLED;
+use crate::error::MeilisearchHttpError;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "camelCase")]
-enum TaskType {
+pub enum TaskType {
     IndexCreation,
     IndexUpdate,
     IndexDeletion,
     DocumentAddition,
     DocumentPartial,
     DocumentDeletion,
+    DocumentEdition,
     SettingsUpdate,
     ClearAll,
 }
 
-impl From<TaskContent> for TaskType {
-    fn from(other: TaskContent) -> Self {
+impl From<&TaskContent> for TaskType {
+    fn from(other: &TaskContent) -> Self {
         match other {
             TaskContent::DocumentAddition {
                 merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
@@ -38,7 +41,11 @@ impl From<TaskContent> for TaskType {
                 ..
             } => TaskType::DocumentPartial,
             TaskContent::DocumentDeletion(DocumentDeletion::Clear) => TaskType::ClearAll,
-            TaskContent::DocumentDeletion(DocumentDeletion::Ids(_)) => TaskType::DocumentDeletion,
+            TaskContent::DocumentDeletion(DocumentDeletion::Ids(_))
+            | TaskContent::DocumentDeletion(DocumentDeletion::Filter(_)) => {
+                TaskType::DocumentDeletion
+            }
+            TaskContent::DocumentEdition { .. } => TaskType::DocumentEdition,
             TaskContent::SettingsUpdate { .. } => TaskType::SettingsUpdate,
             TaskContent::IndexDeletion => TaskType::IndexDeletion,
             TaskContent::IndexCreation { .. } => TaskType::IndexCreation,
@@ -48,13 +55,64 @@ impl From<TaskContent> for TaskType {
     }
 }
 
-#[derive(Debug, Serialize)]
+impl FromStr for TaskType {
+    type Err = MeilisearchHttpError;
+
+    fn from_str(task_type: &str) -> Result<Self, Self::Err> {
+        match task_type {
+            "indexCreation" => Ok(TaskType::IndexCreation),
+            "indexUpdate" => Ok(TaskType::IndexUpdate),
+            "indexDeletion" => Ok(TaskType::IndexDeletion),
+            "documentAddition" => Ok(TaskType::DocumentAddition),
+            "documentPartial" => Ok(TaskType::DocumentPartial),
+            "documentDeletion" => Ok(TaskType::DocumentDeletion),
+            "documentEdition" => Ok(TaskType::DocumentEdition),
+            "settingsUpdate" => Ok(TaskType::SettingsUpdate),
+            "clearAll" => Ok(TaskType::ClearAll),
+            _ => Err(MeilisearchHttpError::InvalidTaskTypesFilter(
+                task_type.to_string(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "camelCase")]
-enum TaskStatus {
+pub enum TaskStatus {
     Enqueued,
     Processing,
     Succeeded,
     Failed,
+    Canceled,
+}
+
+impl From<&TaskEvent> for TaskStatus {
+    fn from(event: &TaskEvent) -> Self {
+        match event {
+            TaskEvent::Created(_) | TaskEvent::Batched { .. } => TaskStatus::Enqueued,
+            TaskEvent::Processing(_) => TaskStatus::Processing,
+            TaskEvent::Succeded { .. } => TaskStatus::Succeeded,
+            TaskEvent::Failed { .. } => TaskStatus::Failed,
+            TaskEvent::Canceled(_) => TaskStatus::Canceled,
+        }
+    }
+}
+
+impl FromStr for TaskStatus {
+    type Err = MeilisearchHttpError;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "enqueued" => Ok(TaskStatus::Enqueued),
+            "processing" => Ok(TaskStatus::Processing),
+            "succeeded" => Ok(TaskStatus::Succeeded),
+            "failed" => Ok(TaskStatus::Failed),
+            "canceled" => Ok(TaskStatus::Canceled),
+            _ => Err(MeilisearchHttpError::InvalidTaskStatusesFilter(
+                status.to_string(),
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -79,6 +137,17 @@ enum TaskDetails {
         deleted_documents: Option<u64>,
     },
     #[serde(rename_all = "camelCase")]
+    DocumentDeletionByFilter {
+        original_filter: Value,
+        deleted_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentEdition {
+        original_filter: Option<Value>,
+        edits: Vec<DocumentEdit>,
+        edited_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
     ClearAll { deleted_documents: Option<u64> },
 }
 
@@ -154,7 +223,8 @@ pub struct TaskView {
     #[serde(serialize_with = "time::serde::rfc3339::option::serialize")]
     finished_at: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    batch_uid: Option<Option<BatchId>>,
+    batch_uid: Option<BatchId>,
+    priority: TaskPriority,
 }
 
 impl From<Task> for TaskView {
@@ -164,6 +234,7 @@ impl From<Task> for TaskView {
             index_uid,
             content,
             events,
+            priority,
         } = task;
 
         let (task_type, mut details) = match content {
@@ -192,6 +263,21 @@ impl From<Task> for TaskView {
                     deleted_documents: None,
                 }),
             ),
+            TaskContent::DocumentDeletion(DocumentDeletion::Filter(filter)) => (
+                TaskType::DocumentDeletion,
+                Some(TaskDetails::DocumentDeletionByFilter {
+                    original_filter: filter.clone(),
+                    deleted_documents: None,
+                }),
+            ),
+            TaskContent::DocumentEdition { filter, edits } => (
+                TaskType::DocumentEdition,
+                Some(TaskDetails::DocumentEdition {
+                    original_filter: filter,
+                    edits,
+                    edited_documents: None,
+                }),
+            ),
             TaskContent::DocumentDeletion(DocumentDeletion::Clear) => (
                 TaskType::ClearAll,
                 Some(TaskDetails::ClearAll {
@@ -245,10 +331,25 @@ impl From<Task> for TaskView {
                         Some(TaskDetails::DocumentDeletion {
                             ref mut deleted_documents,
                             ..
+                        })
+                        | Some(TaskDetails::DocumentDeletionByFilter {
+                            ref mut deleted_documents,
+                            ..
                         }),
                     ) => {
                         deleted_documents.replace(*docs);
                     }
+                    (
+                        TaskResult::DocumentEdition {
+                            edited_documents: docs,
+                        },
+                        Some(TaskDetails::DocumentEdition {
+                            ref mut edited_documents,
+                            ..
+                        }),
+                    ) => {
+                        edited_documents.replace(*docs);
+                    }
                     (
                         TaskResult::ClearAll {
                             deleted_documents: docs,
@@ -268,6 +369,10 @@ impl From<Task> for TaskView {
                     Some(TaskDetails::DocumentDeletion {
                         ref mut deleted_documents,
                         ..
+                    })
+                    | Some(TaskDetails::DocumentDeletionByFilter {
+                        ref mut deleted_documents,
+                        ..
                     }) => {
                         deleted_documents.replace(0);
                     }
@@ -283,10 +388,17 @@ impl From<Task> for TaskView {
                     }) => {
                         indexed_documents.replace(0);
                     }
+                    Some(TaskDetails::DocumentEdition {
+                        ref mut edited_documents,
+                        ..
+                    }) => {
+                        edited_documents.replace(0);
+                    }
                     _ => (),
                 }
                 (TaskStatus::Failed, Some(error.clone()), Some(*timestamp))
             }
+            TaskEvent::Canceled(timestamp) => (TaskStatus::Canceled, None, Some(*timestamp)),
         };
 
         let enqueued_at = match events.first() {
@@ -301,15 +413,10 @@ impl From<Task> for TaskView {
 
         let duration = finished_at.zip(started_at).map(|(tf, ts)| (tf - ts));
 
-        let batch_uid = if AUTOBATCHING_ENABLED.load(std::sync::atomic::Ordering::Relaxed) {
-            let id = events.iter().find_map(|e| match e {
-                TaskEvent::Batched { batch_id, .. } => Some(*batch_id),
-                _ => None,
-            });
-            Some(id)
-        } else {
-            None
-        };
+        let batch_uid = events.iter().find_map(|e| match e {
+            TaskEvent::Batched { batch_id, .. } => Some(*batch_id),
+            _ => None,
+        });
 
         Self {
             uid: id,
@@ -323,6 +430,7 @@ impl From<Task> for TaskView {
             started_at,
             finished_at,
             batch_uid,
+            priority,
         }
     }
 }
@@ -338,6 +446,31 @@ impl From<Vec<TaskView>> for TaskListView {
     }
 }
 
+/// A page of tasks. `next` is the id to pass as `from` to fetch the following page, if any.
+#[derive(Debug, Serialize)]
+pub struct PaginatedTaskListView {
+    results: Vec<TaskView>,
+    limit: usize,
+    from: Option<TaskId>,
+    next: Option<TaskId>,
+}
+
+impl PaginatedTaskListView {
+    pub fn new(
+        results: Vec<TaskView>,
+        limit: usize,
+        from: Option<TaskId>,
+        next: Option<TaskId>,
+    ) -> Self {
+        Self {
+            results,
+            limit,
+            from,
+            next,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SummarizedTaskView {
@@ -367,7 +500,7 @@ impl From<Task> for SummarizedTaskView {
             uid: other.id,
             index_uid: other.index_uid.to_string(),
             status: TaskStatus::Enqueued,
-            task_type: other.content.into(),
+            task_type: (&other.content).into(),
             enqueued_at,
         }
     }
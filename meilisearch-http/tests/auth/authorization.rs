@@ -11,11 +11,19 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
         hashmap! {
             ("POST",    "/indexes/products/search") =>                         hashset!{"search", "*"},
             ("GET",     "/indexes/products/search") =>                         hashset!{"search", "*"},
+            ("POST",    "/indexes/products/facet-search") =>                   hashset!{"search", "*"},
+            ("POST",    "/multi-search") =>                                    hashset!{"search", "*"},
             ("POST",    "/indexes/products/documents") =>                      hashset!{"documents.add", "*"},
             ("GET",     "/indexes/products/documents") =>                      hashset!{"documents.get", "*"},
+            ("POST",    "/indexes/products/documents/edit") =>                 hashset!{"documents.add", "*"},
+            ("POST",    "/indexes/products/documents/fetch") =>                hashset!{"documents.get", "*"},
             ("GET",     "/indexes/products/documents/0") =>                    hashset!{"documents.get", "*"},
             ("DELETE",  "/indexes/products/documents/0") =>                    hashset!{"documents.delete", "*"},
             ("GET",     "/tasks") =>                                           hashset!{"tasks.get", "*"},
+            ("POST",    "/tasks/0/cancel") =>                                  hashset!{"tasks.cancel", "*"},
+            ("DELETE",  "/tasks") =>                                           hashset!{"tasks.delete", "*"},
+            // the unknown parameter makes the request fail after authentication instead of streaming forever.
+            ("GET",     "/tasks/stream?unknown=true") =>                       hashset!{"tasks.get", "*"},
             ("GET",     "/indexes/products/tasks") =>                          hashset!{"tasks.get", "*"},
             ("GET",     "/indexes/products/tasks/0") =>                        hashset!{"tasks.get", "*"},
             ("PUT",     "/indexes/products/") =>                               hashset!{"indexes.update", "*"},
@@ -42,6 +50,7 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("POST",    "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/stop-words") =>            hashset!{"settings.update", "*"},
             ("POST",    "/indexes/products/settings/synonyms") =>              hashset!{"settings.update", "*"},
+            ("GET",     "/indexes/products/export") =>                         hashset!{"documents.get", "*"},
             ("GET",     "/indexes/products/stats") =>                          hashset!{"stats.get", "*"},
             ("GET",     "/stats") =>                                           hashset!{"stats.get", "*"},
             ("POST",    "/dumps") =>                                           hashset!{"dumps.create", "*"},
@@ -573,3 +573,53 @@ async fn error_access_modified_token() {
     assert_eq!(response, INVALID_RESPONSE.clone());
     assert_eq!(code, 403);
 }
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn multi_search_tenant_token() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+    let index = server.index("sales");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    index.wait_task(1).await;
+    let index = server.index("products");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(2).await;
+    drop(index);
+
+    let (response, code) = server.add_api_key(ACCEPTED_KEYS[0].clone()).await;
+    assert_eq!(code, 201);
+    let key = response["key"].as_str().unwrap();
+
+    let tenant_token = hashmap! {
+        "searchRules" => json!({"sales": {"filter": "color = blue"}}),
+        "exp" => json!((OffsetDateTime::now_utc() + Duration::hours(1)).unix_timestamp())
+    };
+    let web_token = generate_tenant_token(&key, tenant_token);
+    server.use_api_key(&web_token);
+
+    // the tenant token filter is applied to the queries on `sales`.
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "sales", "query": {}},
+            {"indexUid": "sales", "query": {"filter": "color = yellow"}},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"][0]["hits"].as_array().unwrap().len(), 3);
+    assert_eq!(response["results"][1]["hits"].as_array().unwrap().len(), 1);
+
+    // a single query on an index outside of the search rules rejects the whole request.
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "sales", "query": {}},
+            {"indexUid": "products", "query": {}},
+        ]}))
+        .await;
+    assert_eq!(response, INVALID_RESPONSE.clone());
+    assert_eq!(code, 403);
+}
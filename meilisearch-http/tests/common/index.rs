@@ -105,6 +105,14 @@ impl Index<'_> {
         self.service.put(url, documents).await
     }
 
+    pub async fn deep_merge_documents(&self, documents: Value) -> (Value, StatusCode) {
+        let url = format!(
+            "/indexes/{}/documents?deepMerge=true",
+            encode(self.uid.as_ref())
+        );
+        self.service.put(url, documents).await
+    }
+
     pub async fn wait_task(&self, update_id: u64) -> Value {
         // try 10 times to get status, or panic to not wait forever
         let url = format!("/tasks/{}", update_id);
@@ -126,6 +134,11 @@ impl Index<'_> {
         self.service.get(url).await
     }
 
+    pub async fn cancel_task(&self, update_id: u64) -> (Value, StatusCode) {
+        let url = format!("/tasks/{}/cancel", update_id);
+        self.service.post(url, json!({})).await
+    }
+
     pub async fn list_tasks(&self) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/tasks", self.uid);
         self.service.get(url).await
@@ -170,6 +183,26 @@ impl Index<'_> {
         self.service.delete(url).await
     }
 
+    pub async fn export(&self, accept: &str) -> (String, StatusCode) {
+        let url = format!("/indexes/{}/export", encode(self.uid.as_ref()));
+        self.service.get_raw(url, accept).await
+    }
+
+    pub async fn fetch_documents(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/fetch", encode(self.uid.as_ref()));
+        self.service.post(url, query).await
+    }
+
+    pub async fn edit_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/edit", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
+    pub async fn delete_by_filter(&self, filter: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/delete", encode(self.uid.as_ref()));
+        self.service.post(url, json!({ "filter": filter })).await
+    }
+
     pub async fn delete_batch(&self, ids: Vec<u64>) -> (Value, StatusCode) {
         let url = format!(
             "/indexes/{}/documents/delete-batch",
@@ -225,6 +258,11 @@ impl Index<'_> {
         self.service.post(url, query).await
     }
 
+    pub async fn facet_search(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/facet-search", encode(self.uid.as_ref()));
+        self.service.post(url, query).await
+    }
+
     pub async fn search_get(&self, query: Value) -> (Value, StatusCode) {
         let params = serde_url_params::to_string(&query).unwrap();
         let url = format!("/indexes/{}/search?{}", encode(self.uid.as_ref()), params);
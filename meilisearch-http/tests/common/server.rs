@@ -119,6 +119,18 @@ impl Server {
         self.service.get("/tasks").await
     }
 
+    pub async fn multi_search(&self, queries: Value) -> (Value, StatusCode) {
+        self.service.post("/multi-search", queries).await
+    }
+
+    pub async fn tasks_filter(&self, filter: &str) -> (Value, StatusCode) {
+        self.service.get(format!("/tasks?{}", filter)).await
+    }
+
+    pub async fn delete_tasks(&self, filter: &str) -> (Value, StatusCode) {
+        self.service.delete(format!("/tasks?{}", filter)).await
+    }
+
     pub async fn get_dump_status(&self, uid: &str) -> (Value, StatusCode) {
         self.service.get(format!("/dumps/{}/status", uid)).await
     }
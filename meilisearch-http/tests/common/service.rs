@@ -1,4 +1,7 @@
+use actix_web::body::MessageBody;
 use actix_web::{http::StatusCode, test};
+use bytes::Bytes;
+use futures::Stream;
 use meilisearch_auth::AuthController;
 use meilisearch_lib::MeiliSearch;
 use serde_json::Value;
@@ -90,6 +93,63 @@ impl Service {
         (response, status_code)
     }
 
+    /// Sends a get request to a streaming route, and returns the chunks of the response body as
+    /// they are received.
+    pub async fn get_stream(
+        &self,
+        url: impl AsRef<str>,
+    ) -> (impl Stream<Item = Bytes> + Unpin, StatusCode) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let mut req = test::TestRequest::get().uri(url.as_ref());
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        let req = req.to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let mut body = Box::pin(res.into_body());
+        let chunks = futures::stream::poll_fn(move |cx| {
+            body.as_mut()
+                .poll_next(cx)
+                .map(|chunk| chunk.and_then(Result::ok))
+        });
+        (chunks, status_code)
+    }
+
+    /// Sends a get request with an `accept` header, and returns the raw body of the response.
+    pub async fn get_raw(&self, url: impl AsRef<str>, accept: &str) -> (String, StatusCode) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let mut req = test::TestRequest::get()
+            .uri(url.as_ref())
+            .insert_header(("accept", accept));
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        let req = req.to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let body = test::read_body(res).await;
+        (String::from_utf8(body.to_vec()).unwrap(), status_code)
+    }
+
     pub async fn put(&self, url: impl AsRef<str>, body: Value) -> (Value, StatusCode) {
         let app = test::init_service(create_app!(
             &self.meilisearch,
@@ -810,6 +810,98 @@ async fn update_document() {
     );
 }
 
+#[actix_rt::test]
+async fn deep_merge_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "shoe", "price": { "amount": 10, "currency": "EUR" }, "tags": ["a"] },
+        { "id": 2, "title": "hat", "price": { "amount": 5, "currency": "USD" } },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let documents = json!([
+        { "id": 1, "price": { "amount": 12 }, "tags": ["b"] },
+        { "id": 1, "title": null, "stock": { "count": 3 } },
+        { "id": 3, "title": "sock" },
+    ]);
+    let (response, code) = index.deep_merge_documents(documents).await;
+    assert_eq!(code, 202, "{}", response);
+    let task = index.wait_task(1).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+    assert_eq!(task["type"], "documentPartial");
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({
+            "id": 1,
+            "price": { "amount": 12, "currency": "EUR" },
+            "tags": ["b"],
+            "stock": { "count": 3 },
+        })
+    );
+
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": 2, "title": "hat", "price": { "amount": 5, "currency": "USD" } })
+    );
+
+    let (response, code) = index.get_document(3, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "id": 3, "title": "sock" }));
+}
+
+#[actix_rt::test]
+async fn deep_merge_documents_with_prefixed_ids() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(Some("id")).await;
+
+    let documents = json!([
+        { "id": 1, "doggo": { "name": "bob" } },
+        { "id": 10, "doggo": { "name": "fido" } },
+        { "id": 1, "doggo": { "age": 3 } },
+        { "id": 100, "doggo": { "name": "rex" } },
+        { "id": 10, "doggo": { "name": null, "age": 4 } },
+    ]);
+    let (response, code) = index.deep_merge_documents(documents).await;
+    assert_eq!(code, 202, "{}", response);
+    let task = index.wait_task(1).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+
+    let (response, _) = index.get_document(1, None).await;
+    assert_eq!(
+        response,
+        json!({ "id": 1, "doggo": { "name": "bob", "age": 3 } })
+    );
+    let (response, _) = index.get_document(10, None).await;
+    assert_eq!(response, json!({ "id": 10, "doggo": { "age": 4 } }));
+    let (response, _) = index.get_document(100, None).await;
+    assert_eq!(response, json!({ "id": 100, "doggo": { "name": "rex" } }));
+}
+
+#[actix_rt::test]
+async fn error_deep_merge_with_replace() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/documents?deepMerge=true",
+            json!([{ "id": 1 }]),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
 #[actix_rt::test]
 async fn add_larger_dataset() {
     let server = Server::new().await;
@@ -1099,3 +1191,40 @@ async fn add_documents_with_primary_key_twice() {
     let (response, _code) = index.get_task(1).await;
     assert_eq!(response["status"], "succeeded");
 }
+
+#[actix_rt::test]
+async fn add_documents_wait_for_task() {
+    let server = Server::new().await;
+
+    let documents = json!([{ "id": 1, "content": "foo" }]);
+    let (response, code) = server
+        .service
+        .post("/indexes/test/documents?waitForTask=10000", documents)
+        .await;
+
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["uid"], 0);
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+}
+
+#[actix_rt::test]
+async fn error_add_documents_wait_for_task_timeout() {
+    let server = Server::new().await;
+
+    let documents = json!([{ "id": 1, "content": "foo" }]);
+    let (response, code) = server
+        .service
+        .post("/indexes/test/documents?waitForTask=0", documents)
+        .await;
+
+    let expected_response = json!({
+        "message": "Task `0` did not finish before the timeout. Its status can still be retrieved on the tasks routes.",
+        "code": "task_wait_timeout",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#task_wait_timeout"
+    });
+
+    assert_eq!(response, expected_response);
+    assert_eq!(code, 504);
+}
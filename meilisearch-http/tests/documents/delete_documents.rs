@@ -145,3 +145,48 @@ async fn delete_no_document_batch() {
     assert_eq!(code, 200);
     assert_eq!(response.as_array().unwrap().len(), 3);
 }
+
+#[actix_rt::test]
+async fn delete_documents_by_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["tenant"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "tenant": "a" },
+                { "id": 2, "tenant": "b" },
+                { "id": 3, "tenant": "a" },
+                { "id": 4, "tenant": "c" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.delete_by_filter(json!("tenant = a")).await;
+    assert_eq!(code, 202, "{}", response);
+    let task = index.wait_task(2).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+    assert_eq!(task["type"], "documentDeletion");
+    assert_eq!(task["details"]["originalFilter"], "tenant = a");
+    assert_eq!(task["details"]["deletedDocuments"], 2);
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!([{ "id": 2, "tenant": "b" }, { "id": 4, "tenant": "c" }])
+    );
+
+    // a filter on an attribute that is not filterable fails the task.
+    let (_response, code) = index.delete_by_filter(json!(["id = 2"])).await;
+    assert_eq!(code, 202);
+    let task = index.wait_task(3).await;
+    assert_eq!(task["status"], "failed", "{}", task);
+    assert_eq!(task["details"]["deletedDocuments"], 0);
+}
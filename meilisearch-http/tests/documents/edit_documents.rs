@@ -0,0 +1,90 @@
+use serde_json::json;
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn edit_documents_with_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["tenant"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "tenant": "a", "views": 1, "tags": [] },
+                { "id": 2, "tenant": "b", "views": 1, "tags": [] },
+                { "id": 3, "tenant": "a", "stats": { "likes": 2 } },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .edit_documents(json!({
+            "filter": "tenant = a",
+            "edits": [
+                { "op": "increment", "path": "views", "by": 2 },
+                { "op": "append", "path": "tags", "value": "new" },
+                { "op": "remove", "path": "stats.likes" },
+            ]
+        }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let task = index.wait_task(2).await;
+    assert_eq!(task["status"], "succeeded", "{}", task);
+    assert_eq!(task["type"], "documentEdition");
+    assert_eq!(task["details"]["originalFilter"], "tenant = a");
+    assert_eq!(task["details"]["editedDocuments"], 2);
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!([
+            { "id": 1, "tenant": "a", "views": 3, "tags": ["new"] },
+            { "id": 2, "tenant": "b", "views": 1, "tags": [] },
+            { "id": 3, "tenant": "a", "stats": {}, "views": 2, "tags": ["new"] },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn edit_documents_invalid_edit() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "hello" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    // the primary key can't be edited.
+    let (_response, code) = index
+        .edit_documents(json!({ "edits": [{ "op": "set", "path": "id", "value": 2 }] }))
+        .await;
+    assert_eq!(code, 202);
+    let task = index.wait_task(1).await;
+    assert_eq!(task["status"], "failed", "{}", task);
+    assert_eq!(task["error"]["code"], "invalid_document_edit");
+    assert_eq!(task["details"]["editedDocuments"], 0);
+
+    let (_response, code) = index
+        .edit_documents(json!({ "edits": [{ "op": "increment", "path": "title", "by": 1 }] }))
+        .await;
+    assert_eq!(code, 202);
+    let task = index.wait_task(2).await;
+    assert_eq!(task["status"], "failed", "{}", task);
+    assert_eq!(task["error"]["code"], "invalid_document_edit");
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "id": 1, "title": "hello" }));
+
+    let (response, code) = index
+        .edit_documents(json!({ "edits": [{ "op": "rename", "path": "title" }] }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+}
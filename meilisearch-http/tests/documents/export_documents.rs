@@ -0,0 +1,115 @@
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn export_documents_ndjson() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    // more documents than an export chunk.
+    let documents: Vec<Value> = (0..1500)
+        .map(|id| json!({ "id": id, "title": format!("title {}", id) }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(0).await;
+
+    for accept in ["application/x-ndjson", "*/*"] {
+        let (body, code) = index.export(accept).await;
+        assert_eq!(code, 200, "{}", body);
+
+        let exported: Vec<Value> = body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(exported, documents);
+    }
+}
+
+#[actix_rt::test]
+async fn export_documents_with_vectors() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!([
+        { "id": 1, "title": "Shazam!", "_vectors": [1, 0, 0] },
+        { "id": 2, "title": "Captain Marvel", "_vectors": [[0, 1, 0], [0.9, 0.1, 0]] },
+        { "id": 3, "title": "Escape Room" },
+    ]);
+    index.add_documents(documents.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (body, code) = index.export("application/x-ndjson").await;
+    assert_eq!(code, 200, "{}", body);
+    let exported: Vec<Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(json!(exported), documents);
+
+    // the export can be imported again with its vectors.
+    let copy = server.index("copy");
+    copy.add_documents(json!(exported), None).await;
+    copy.wait_task(1).await;
+
+    let (response, code) = copy
+        .search_post(json!({ "vector": [1, 0, 0], "semanticRatio": 1.0 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, [1, 2]);
+
+    // a CSV can't hold the vectors.
+    let (body, code) = index.export("text/csv").await;
+    assert_eq!(code, 200, "{}", body);
+    assert!(!body.contains("_vectors"), "{}", body);
+}
+
+#[actix_rt::test]
+async fn export_documents_csv() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "displayedAttributes": ["id", "title", "tags"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "hello, world", "tags": ["a"], "secret": "hidden" },
+                { "id": 2, "title": "bye" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (body, code) = index.export("text/csv, application/x-ndjson").await;
+    assert_eq!(code, 200, "{}", body);
+    assert_eq!(
+        body,
+        "id,title,tags\n1,\"hello, world\",\"[\"\"a\"\"]\"\n2,bye,\n"
+    );
+}
+
+#[actix_rt::test]
+async fn export_documents_errors() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (body, code) = index.export("application/x-ndjson").await;
+    assert_eq!(code, 404, "{}", body);
+    let response: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["code"], "index_not_found");
+
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (body, code) = index.export("application/xml").await;
+    assert_eq!(code, 406, "{}", body);
+    let response: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["code"], "invalid_accept");
+}
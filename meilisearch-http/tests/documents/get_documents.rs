@@ -56,6 +56,37 @@ async fn get_document() {
     );
 }
 
+#[actix_rt::test]
+async fn get_and_delete_documents_named_like_routes() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!([
+        { "id": "delete", "content": "foo" },
+        { "id": "delete-batch", "content": "bar" },
+        { "id": "fetch", "content": "baz" },
+        { "id": "edit", "content": "qux" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    for (task_id, id) in ["delete", "delete-batch", "fetch", "edit"]
+        .iter()
+        .enumerate()
+    {
+        let url = format!("/indexes/test/documents/{}", id);
+        let (response, code) = server.service.get(&url).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["id"], *id);
+
+        let (response, code) = server.service.delete(&url).await;
+        assert_eq!(code, 202, "{}", response);
+        index.wait_task(task_id as u64 + 1).await;
+
+        let (response, code) = server.service.get(&url).await;
+        assert_eq!(code, 404, "{}", response);
+    }
+}
+
 #[actix_rt::test]
 async fn error_get_unexisting_index_all_documents() {
     let server = Server::new().await;
@@ -305,3 +336,99 @@ async fn get_documents_displayed_attributes() {
     assert_eq!(response.as_object().unwrap().keys().count(), 1);
     assert!(response.as_object().unwrap().get("gender").is_some());
 }
+
+#[actix_rt::test]
+async fn fetch_documents_with_filter_and_fields() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    let documents = json!([
+        { "id": 1, "color": "red", "doggo": { "name": "bob", "age": 3 } },
+        { "id": 2, "color": "blue", "doggo": { "name": "fido", "age": 4 } },
+        { "id": 3, "color": "red", "doggo": { "name": "rex", "age": 5 } },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .fetch_documents(json!({ "filter": "color = red", "fields": ["id", "doggo.name"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["total"], 2);
+    assert_eq!(
+        response["results"],
+        json!([
+            { "id": 1, "doggo": { "name": "bob" } },
+            { "id": 3, "doggo": { "name": "rex" } },
+        ])
+    );
+    assert!(response.get("nextCursor").is_none());
+}
+
+#[actix_rt::test]
+async fn fetch_documents_with_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let mut ids = Vec::new();
+    let (mut response, code) = index.fetch_documents(json!({ "limit": 30 })).await;
+    assert_eq!(code, 200, "{}", response);
+    loop {
+        assert_eq!(response["total"], 77);
+        for document in response["results"].as_array().unwrap() {
+            ids.push(document["id"].as_u64().unwrap());
+        }
+
+        let cursor = match response["nextCursor"].as_str() {
+            Some(cursor) => cursor.to_string(),
+            None => break,
+        };
+        let (next, code) = index
+            .fetch_documents(json!({ "limit": 30, "cursor": cursor }))
+            .await;
+        assert_eq!(code, 200, "{}", next);
+        response = next;
+    }
+
+    assert_eq!(ids, (0..77).collect::<Vec<_>>());
+}
+
+#[actix_rt::test]
+async fn fetch_documents_huge_limit() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let (response, code) = index.fetch_documents(json!({ "limit": usize::MAX })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 77);
+    assert!(response.get("nextCursor").is_none());
+}
+
+#[actix_rt::test]
+async fn fetch_documents_zero_limit() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let (response, code) = index.fetch_documents(json!({ "limit": 0 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["total"], 77);
+    assert_eq!(response["results"], json!([]));
+    assert!(response.get("nextCursor").is_none());
+}
+
+#[actix_rt::test]
+async fn fetch_documents_invalid_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.fetch_documents(json!({ "cursor": "hello" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_documents_cursor");
+}
@@ -1,3 +1,5 @@
 mod add_documents;
 mod delete_documents;
+mod edit_documents;
+mod export_documents;
 mod get_documents;
@@ -0,0 +1,135 @@
+use crate::common::Server;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "Air Max", "brand": "Nike", "color": "red" },
+        { "id": 2, "title": "Air Force", "brand": "Nike", "color": "blue" },
+        { "id": 3, "title": "Superstar", "brand": "Adidas", "color": "red" },
+        { "id": 4, "title": "Gel Lyte", "brand": "Asics", "color": "blue" },
+        { "id": 5, "title": "Air Jordan", "brand": "Nike", "color": "red" },
+    ])
+});
+
+#[actix_rt::test]
+async fn facet_search_prefix_and_typos() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["brand", "color"]}))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "brand", "facetQuery": "nik" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([{ "value": "Nike", "count": 3 }])
+    );
+    assert_eq!(response["facetQuery"], "nik");
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "brand", "facetQuery": "adiddas" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([{ "value": "Adidas", "count": 1 }])
+    );
+
+    // without query, all the values are returned, the most frequent first.
+    let (response, code) = index.facet_search(json!({ "facetName": "brand" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([
+            { "value": "Nike", "count": 3 },
+            { "value": "Adidas", "count": 1 },
+            { "value": "Asics", "count": 1 },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn facet_search_counts_the_candidates() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["brand", "color"]}))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "brand", "facetQuery": "a", "filter": "color = red" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([{ "value": "Adidas", "count": 1 }])
+    );
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "brand", "q": "air" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([{ "value": "Nike", "count": 3 }])
+    );
+}
+
+#[actix_rt::test]
+async fn facet_search_more_than_a_hundred_values() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["brand"]}))
+        .await;
+    let documents: Vec<_> = (0..150)
+        .map(|id| json!({ "id": id, "brand": format!("brand {}", id) }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.facet_search(json!({ "facetName": "brand" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["facetHits"].as_array().unwrap().len(), 150);
+
+    // the values that are not among the 100 first ones are found too.
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "brand", "facetQuery": "brand 149" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let hits = response["facetHits"].as_array().unwrap();
+    assert!(
+        hits.contains(&json!({ "value": "brand 149", "count": 1 })),
+        "{}",
+        response
+    );
+}
+
+#[actix_rt::test]
+async fn facet_search_not_filterable_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .facet_search(json!({ "facetName": "brand", "facetQuery": "nik" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_facet_name");
+}
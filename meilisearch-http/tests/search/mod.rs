@@ -2,7 +2,10 @@
 // should be tested in its own module to isolate tests and keep the tests readable.
 
 mod errors;
+mod facet_search;
 mod formatted;
+mod multi;
+mod pagination;
 
 use crate::common::Server;
 use once_cell::sync::Lazy;
@@ -606,3 +609,573 @@ async fn search_is_hard_limited() {
         )
         .await;
 }
+
+#[actix_rt::test]
+async fn search_exhaustive_counts() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    let documents: Vec<_> = (0..50)
+        .map(|i| json!({ "id": i, "text": "I am unique!", "color": i % 2 }))
+        .collect();
+    index.add_documents(documents.into(), None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({ "limit": 5, "facetsDistribution": ["color"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["nbHits"], 50);
+                assert_eq!(response["exhaustiveNbHits"], true);
+                assert_eq!(response["exhaustiveFacetsCount"], true);
+                assert_eq!(
+                    response["facetsDistribution"]["color"],
+                    json!({ "0": 25, "1": 25 })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(json!({ "q": "unique", "limit": 5 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["exhaustiveNbHits"], false);
+        })
+        .await;
+
+    index
+        .search(
+            json!({ "q": "unique", "limit": 5, "exhaustiveCounts": true, "facetsDistribution": ["color"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 5);
+                assert_eq!(response["nbHits"], 50);
+                assert_eq!(response["exhaustiveNbHits"], true);
+                assert_eq!(response["exhaustiveFacetsCount"], true);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_facet_stats() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["price", "title", "doggos.age"]}))
+        .await;
+    let documents = json!([
+        { "id": 1, "title": "bike", "price": 120.5, "doggos": [{ "age": 2 }] },
+        { "id": 2, "title": "car", "price": 15000, "doggos": [{ "age": 8 }, { "age": 1 }] },
+        { "id": 3, "title": "bike", "price": 89 },
+        { "id": 4, "title": "scooter", "price": "cheap" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({ "facetsDistribution": ["price", "title", "doggos.age"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["facetStats"],
+                    json!({
+                        "price": { "min": 89.0, "max": 15000.0 },
+                        "doggos.age": { "min": 1.0, "max": 8.0 },
+                    })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "filter": "title = bike", "facetsDistribution": ["*"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["facetStats"],
+                    json!({
+                        "price": { "min": 89.0, "max": 120.5 },
+                        "doggos.age": { "min": 2.0, "max": 2.0 },
+                    })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(json!({ "q": "bike" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("facetStats").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_geo_filters() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["_geo"]}))
+        .await;
+    let documents = json!([
+        { "id": 1, "city": "Lille", "_geo": { "lat": 50.629973, "lng": 3.056944 } },
+        { "id": 2, "city": "Paris", "_geo": { "lat": 48.856613, "lng": 2.352222 } },
+        { "id": 3, "city": "Marseille", "_geo": { "lat": 43.296482, "lng": 5.36978 } },
+        { "id": 4, "city": "Suva", "_geo": { "lat": -18.124809, "lng": 178.450079 } },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let ids = |response: &Value| {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([51, 4], [48, 2])" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![1, 2]);
+            },
+        )
+        .await;
+
+    // the box crosses the antimeridian.
+    index
+        .search(
+            json!({ "filter": ["_geoBoundingBox([0, -170], [-30, 170])"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![4]);
+            },
+        )
+        .await;
+
+    // the distance is reported when the geo point comes from the filter.
+    index
+        .search(
+            json!({ "filter": "_geoRadius(50.629973, 3.056944, 1000)" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![1]);
+                assert_eq!(response["hits"][0]["_geoDistance"], 0);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_geo_bounding_box_quoted_values() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["_geo", "city"]}))
+        .await;
+    let documents = json!([
+        { "id": 1, "city": "Lille", "_geo": { "lat": "50.629973", "lng": "3.056944" } },
+        { "id": 2, "city": "Paris", "_geo": { "lat": 48.856613, "lng": 2.352222 } },
+        { "id": 3, "city": "Marseille", "_geo": { "lat": "43.296482", "lng": 5.36978 } },
+        { "id": 4, "city": "_geoBoundingBox([51, 4], [48, 2])" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let ids = |response: &Value| {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // the coordinates indexed as strings are not matched by the bounding boxes.
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([51, 4], [48, 2])" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![2]);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([44, 6], [43, 5])" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), Vec::<u64>::new());
+            },
+        )
+        .await;
+
+    // a bounding box in a quoted value is not rewritten.
+    index
+        .search(
+            json!({ "filter": "city = \"_geoBoundingBox([51, 4], [48, 2])\"" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![4]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_ranking_overrides() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "captain", "overview": "a movie" },
+        { "id": 2, "title": "a movie", "overview": "captain marvel" },
+        { "id": 3, "title": "a movie", "overview": "marvel" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let ids = |response: &Value| {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // the words rule ranks the hit containing both words first.
+    index
+        .search(json!({ "q": "captain marvel" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(ids(&response)[0], 2);
+        })
+        .await;
+
+    // the attribute rule moved first ranks the hit matching in the title first.
+    index
+        .search(
+            json!({
+                "q": "captain marvel",
+                "rankingRules": ["attribute", "words", "typo", "proximity", "sort", "exactness"],
+            }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response)[0], 1);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "q": "captain", "attributesToSearchOn": ["title"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![1]);
+                assert_eq!(response["nbHits"], 1);
+                assert_eq!(response["exhaustiveNbHits"], true);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "q": "captain", "attributesToSearchOn": ["unknown"] }),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_attributes_to_search_on");
+            },
+        )
+        .await;
+
+    // the ranking rules must be a reordering of the ones of the index.
+    index
+        .search(
+            json!({ "q": "captain", "rankingRules": ["words"] }),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_search_ranking_rules");
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_ranking_score() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "captain marvel" },
+        { "id": 2, "title": "captain" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "captain marvel", "showRankingScore": true }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits[0]["id"], 1);
+    let score = |hit: &Value| hit["_rankingScore"].as_f64().unwrap();
+    assert!(score(&hits[0]) > score(&hits[1]));
+    assert!(score(&hits[0]) <= 1.0 && score(&hits[1]) >= 0.0);
+    assert!(hits[0].get("_rankingScoreDetails").is_none());
+
+    let (response, code) = index
+        .search_post(json!({ "q": "captain marvel", "showRankingScoreDetails": true }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let hits = response["hits"].as_array().unwrap();
+    assert!(hits[0].get("_rankingScore").is_none());
+    let details = &hits[1]["_rankingScoreDetails"];
+    assert_eq!(
+        details["words"],
+        json!({ "order": 0, "matchingWords": 1, "maxMatchingWords": 2, "score": 0.5 })
+    );
+    assert_eq!(details["typo"]["order"], 1);
+    assert_eq!(details["proximity"]["order"], 2);
+    assert_eq!(details["attribute"]["order"], 3);
+    assert_eq!(details["exactness"]["order"], 4);
+
+    // the hits are not scored when it is not requested.
+    let (response, code) = index.search_post(json!({ "q": "captain" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["hits"][0].get("_rankingScore").is_none());
+}
+
+#[actix_rt::test]
+async fn search_matching_strategy() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({ "q": "captain unicorn" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+        })
+        .await;
+
+    index
+        .search(
+            json!({ "q": "captain unicorn", "matchingStrategy": "last" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "q": "captain unicorn", "matchingStrategy": "all" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_hybrid() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "Shazam!", "_vectors": [1, 0, 0] },
+        { "id": 2, "title": "Captain Marvel", "_vectors": [0, 1, 0] },
+        { "id": 3, "title": "Captain Planet", "_vectors": [[0, 0, 1], [0.9, 0.1, 0]] },
+        { "id": 4, "title": "Escape Room" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let ids = |response: &Value| {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // a fully semantic search ranks the documents by the similarity of their best vector.
+    let (response, code) = index
+        .search_post(json!({ "vector": [1, 0, 0], "semanticRatio": 1.0, "limit": 3 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![1, 3, 2]);
+    assert!(response["hits"][0].get("_vectors").is_none());
+
+    // without query words, only the documents having a vector are ranked.
+    let (response, code) = index.search_post(json!({ "vector": [1, 0, 0] })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![1, 3, 2]);
+    assert_eq!(response["nbHits"], 3);
+
+    // the documents matching the query stay first when the keyword ranking weights more.
+    let (response, code) = index
+        .search_post(json!({ "q": "captain", "vector": [1, 0, 0], "semanticRatio": 0.2 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let mut first_ids = ids(&response)[..2].to_vec();
+    first_ids.sort_unstable();
+    assert_eq!(first_ids, vec![2, 3]);
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1, 0, 0], "semanticRatio": 1.5 }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_semantic_ratio");
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1, 0, 0], "sort": ["id:asc"] }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_sort");
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1, 0, 0], "attributesToSearchOn": ["title"] }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+
+    let (response, code) = index.search_post(json!({ "vector": [1, 0] })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_vector_dimensions");
+
+    // the vectors are not searchable, while the searchable attributes are still the wildcard.
+    let (response, code) = index.search_post(json!({ "q": "0.9" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 0);
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["searchableAttributes"], json!(["*"]));
+}
+
+#[actix_rt::test]
+async fn search_hybrid_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["id"] }))
+        .await;
+    let documents = json!([
+        { "id": 1, "title": "Shazam!", "_vectors": [1, 0, 0] },
+        { "id": 2, "title": "Captain Marvel", "_vectors": [0, 1, 0] },
+        { "id": 3, "title": "Escape Room" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let ids = |response: &Value| {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // only the documents matching the filter are ranked semantically.
+    let (response, code) = index
+        .search_post(json!({ "vector": [1, 0, 0], "filter": "id > 1" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![2]);
+
+    // the dimensions are only checked against the documents matching the filter.
+    let (response, code) = index
+        .search_post(json!({ "q": "escape", "vector": [1, 0], "filter": "id = 3" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![3]);
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1, 0], "filter": "id < 3" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_vector_dimensions");
+}
+
+#[actix_rt::test]
+async fn search_hybrid_vectors_follow_their_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "Shazam!", "_vectors": [1, 0] },
+        { "id": 2, "title": "Captain Marvel", "_vectors": [0, 1] },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let ids = |response: &Value| {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+    let semantic_search = json!({ "vector": [1, 0], "semanticRatio": 1.0 });
+
+    // a partial update keeps the vectors of the document.
+    index
+        .update_documents(json!([{ "id": 1, "title": "Glass" }]), None)
+        .await;
+    index.wait_task(1).await;
+    let (response, code) = index.search_post(semantic_search.clone()).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![1, 2]);
+
+    // the vectors are neither displayed nor in the fields of the index.
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "id": 1, "title": "Glass" }));
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["displayedAttributes"], json!(["*"]));
+
+    // a replaced document loses its vectors.
+    index
+        .add_documents(json!([{ "id": 1, "title": "Shazam!" }]), None)
+        .await;
+    index.wait_task(2).await;
+    let (response, code) = index.search_post(semantic_search.clone()).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![2]);
+
+    // a deleted document loses its vectors, even if a new document gets its internal id.
+    index.delete_document(2).await;
+    index.wait_task(3).await;
+    index
+        .add_documents(json!([{ "id": 3, "title": "Escape Room" }]), None)
+        .await;
+    index.wait_task(4).await;
+    let (response, code) = index.search_post(semantic_search).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), Vec::<u64>::new());
+}
@@ -0,0 +1,79 @@
+use super::*;
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn multi_search_two_indexes() {
+    let server = Server::new().await;
+    let movies = server.index("movies");
+    movies.add_documents(DOCUMENTS.clone(), None).await;
+    movies.wait_task(0).await;
+    let nested = server.index("nested");
+    nested.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    nested.wait_task(1).await;
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "movies", "query": {"q": "glass"}},
+            {"indexUid": "nested", "query": {"q": "pesti"}},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["indexUid"], "movies");
+    assert_eq!(results[0]["query"], "glass");
+    assert_eq!(results[0]["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(results[0]["hits"][0]["id"], "450465");
+    assert_eq!(results[1]["indexUid"], "nested");
+    assert_eq!(results[1]["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(results[1]["hits"][0]["id"], 852);
+    assert!(response.get("hits").is_none());
+}
+
+#[actix_rt::test]
+async fn multi_search_merge() {
+    let server = Server::new().await;
+    let movies = server.index("movies");
+    movies.add_documents(DOCUMENTS.clone(), None).await;
+    movies.wait_task(0).await;
+    let nested = server.index("nested");
+    nested.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    nested.wait_task(1).await;
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "movies", "query": {"q": "glass"}},
+            {"indexUid": "nested", "query": {"q": "", "limit": 2}},
+        ], "merge": true}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    // the first hit of each query comes before the second hit of any query.
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 3);
+    assert_eq!(hits[0]["_indexUid"], "movies");
+    assert_eq!(hits[0]["id"], "450465");
+    assert_eq!(hits[1]["_indexUid"], "nested");
+    assert_eq!(hits[1]["id"], response["results"][1]["hits"][0]["id"]);
+    assert_eq!(hits[2]["_indexUid"], "nested");
+    assert_eq!(hits[2]["id"], response["results"][1]["hits"][1]["id"]);
+}
+
+#[actix_rt::test]
+async fn multi_search_unexisting_index() {
+    let server = Server::new().await;
+    let movies = server.index("movies");
+    movies.add_documents(DOCUMENTS.clone(), None).await;
+    movies.wait_task(0).await;
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "movies", "query": {"q": "glass"}},
+            {"indexUid": "test", "query": {}},
+        ]}))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use crate::common::Server;
+use serde_json::{json, Value};
+
+#[actix_rt::test]
+async fn cursor_pagination_beyond_hard_limit() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents: Vec<Value> = (0..1200).map(|id| json!({ "id": id })).collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(0).await;
+
+    let mut seen = HashSet::new();
+    let (mut response, code) = index.search_post(json!({ "limit": 500 })).await;
+    assert_eq!(code, 200, "{}", response);
+    loop {
+        for hit in response["hits"].as_array().unwrap() {
+            assert!(
+                seen.insert(hit["id"].as_u64().unwrap()),
+                "{} returned twice",
+                hit
+            );
+        }
+
+        let cursor = match response["nextCursor"].as_str() {
+            Some(cursor) => cursor.to_string(),
+            None => break,
+        };
+        let (next, code) = index
+            .search_post(json!({ "limit": 500, "cursor": cursor }))
+            .await;
+        assert_eq!(code, 200, "{}", next);
+        response = next;
+    }
+
+    assert_eq!(seen.len(), 1200);
+    assert_eq!(response["offset"], 1000);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 200);
+}
+
+#[actix_rt::test]
+async fn invalid_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "cursor": "hello" })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_cursor");
+
+    // the cursor was not returned by a search.
+    let (response, code) = index
+        .search_post(json!({ "cursor": "00000001000000050000000000000000" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_cursor");
+}
+
+#[actix_rt::test]
+async fn tampered_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents: Vec<_> = (0..10).map(|id| json!({ "id": id })).collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "limit": 2 })).await;
+    assert_eq!(code, 200, "{}", response);
+    let cursor = response["nextCursor"].as_str().unwrap().to_string();
+
+    // the position is moved beyond the candidates.
+    let forged = format!("{:08x}{}", 1000, &cursor[8..]);
+    let (response, code) = index.search_post(json!({ "cursor": forged })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_cursor");
+
+    // the cursor is bound to the query that returned it.
+    let (response, code) = index
+        .search_post(json!({ "cursor": cursor, "q": "1" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_cursor");
+
+    let (response, code) = index.search_post(json!({ "cursor": cursor })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["id"], 2);
+}
+
+#[actix_rt::test]
+async fn cursor_with_vector() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "_vectors": [0.1, 0.2] }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [0.1, 0.2], "cursor": "00000001000000000000000000000000" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_cursor");
+}
@@ -312,3 +312,26 @@ async fn set_and_reset_distinct_attribute_with_dedicated_route() {
 
     assert_eq!(response, json!(null));
 }
+
+#[actix_rt::test]
+async fn update_settings_wait_for_task() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/test/settings?waitForTask=10000",
+            json!({ "distinctAttribute": "test" }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["status"], "succeeded");
+
+    let (response, code) = server
+        .service
+        .delete("/indexes/test/settings/distinct-attribute?waitForTask=10000")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["uid"], 1);
+    assert_eq!(response["status"], "succeeded");
+}
@@ -1,7 +1,11 @@
+use crate::common::server::default_settings;
 use crate::common::Server;
-use serde_json::json;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use futures::StreamExt;
+use serde_json::{json, Value};
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
+use tokio::sync::mpsc;
 
 #[actix_rt::test]
 async fn error_get_task_unexisting_index() {
@@ -91,6 +95,404 @@ async fn list_tasks() {
     assert_eq!(response["results"].as_array().unwrap().len(), 2);
 }
 
+#[actix_rt::test]
+async fn error_cancel_unexisting_task() {
+    let server = Server::new().await;
+    let (response, code) = server.index("test").cancel_task(1).await;
+
+    let expected_response = json!({
+        "message": "Task `1` not found.",
+        "code": "task_not_found",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#task_not_found"
+    });
+
+    assert_eq!(response, expected_response);
+    assert_eq!(code, 404);
+}
+
+#[actix_rt::test]
+async fn error_cancel_finished_task() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    let (response, code) = index.cancel_task(0).await;
+
+    let expected_response = json!({
+        "message": "Task `0` cannot be canceled because it is already processing or finished.",
+        "code": "task_not_cancelable",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#task_not_cancelable"
+    });
+
+    assert_eq!(response, expected_response);
+    assert_eq!(code, 409);
+}
+
+#[actix_rt::test]
+async fn cancel_enqueued_task() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    for _ in 0..5 {
+        index
+            .add_documents(
+                serde_json::from_str(include_str!("../assets/test_set.json")).unwrap(),
+                None,
+            )
+            .await;
+    }
+
+    // The last document addition is still waiting behind the others.
+    let (response, code) = index.cancel_task(5).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["status"], "canceled");
+    assert!(response["finishedAt"].is_string());
+
+    index.wait_task(4).await;
+    let (response, code) = index.get_task(5).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "canceled");
+}
+
+#[actix_rt::test]
+async fn list_tasks_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index.add_documents(json!([{"id": 1}]), None).await;
+    index.wait_task(1).await;
+    server.index("other").create(None).await;
+    index.wait_task(2).await;
+
+    let (response, code) = server.tasks_filter("type=documentAddition").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uid"], 1);
+
+    let (response, code) = server
+        .tasks_filter("type=indexCreation&status=succeeded")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+
+    let (response, code) = server.tasks_filter("indexUid=other").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["indexUid"], "other");
+
+    let (response, code) = server
+        .tasks_filter("afterEnqueuedAt=2000-01-01T00:00:00Z&beforeFinishedAt=2000-01-01T00:00:00Z")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["results"].as_array().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn delete_finished_tasks() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.add_documents(json!([{"id": 1}]), None).await;
+    index.add_documents(json!([{"id": 2}]), None).await;
+    index.wait_task(2).await;
+
+    // at least one filter is required
+    let (response, code) = server.delete_tasks("").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+
+    let (response, code) = server.delete_tasks("type=documentAddition").await;
+    assert_eq!(code, 200, "{}", response);
+    // the most recent task is always kept
+    assert_eq!(response["deletedTasks"], 1);
+
+    let (response, code) = index.get_task(1).await;
+    assert_eq!(code, 404, "{}", response);
+
+    let (response, code) = server.tasks().await;
+    assert_eq!(code, 200, "{}", response);
+    let uids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["uid"].clone())
+        .collect();
+    assert_eq!(uids, vec![json!(2), json!(0)]);
+
+    // new tasks never reuse the id of a deleted task
+    index.add_documents(json!([{"id": 3}]), None).await;
+    let (response, code) = index.get_task(3).await;
+    assert_eq!(code, 200, "{}", response);
+}
+
+#[actix_rt::test]
+async fn stream_task_updates() {
+    let server = Server::new().await;
+    let (mut chunks, code) = server
+        .service
+        .get_stream("/tasks/stream?indexUid=test")
+        .await;
+    assert_eq!(code, 200);
+
+    server.index("other").create(None).await;
+    server.index("test").create(None).await;
+
+    // the updates of the `other` index are filtered out
+    for status in ["enqueued", "processing", "succeeded"] {
+        let chunk = chunks.next().await.unwrap();
+        let chunk = std::str::from_utf8(&chunk).unwrap();
+        let data = chunk.strip_prefix("data: ").unwrap().trim_end();
+        let task: Value = serde_json::from_str(data).unwrap();
+        assert_eq!(task["uid"], 1);
+        assert_eq!(task["indexUid"], "test");
+        assert_eq!(task["status"], status);
+    }
+}
+
+#[actix_rt::test]
+async fn webhook_notified_on_task_completion() {
+    // a stub forwarding the authorization header and body of the notifications it receives.
+    let (sender, mut notifications) = mpsc::unbounded_channel();
+    let stub = HttpServer::new(move || {
+        let sender = sender.clone();
+        App::new().route(
+            "/webhook",
+            web::post().to(move |req: HttpRequest, body: web::Json<Value>| {
+                let authorization = req
+                    .headers()
+                    .get("Authorization")
+                    .and_then(|value| value.to_str().ok())
+                    .map(String::from);
+                sender.send((authorization, body.into_inner())).unwrap();
+                async { HttpResponse::Ok().finish() }
+            }),
+        )
+    })
+    .workers(1)
+    .bind("127.0.0.1:0")
+    .unwrap();
+    let address = stub.addrs()[0];
+    actix_rt::spawn(stub.run());
+
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.scheduler_options.task_webhook_url = Some(format!("http://{}/webhook", address));
+    options.scheduler_options.task_webhook_authorization_header = Some("Bearer token".to_string());
+    let server = Server::new_with_options(options).await;
+
+    server.index("test").create(None).await;
+
+    let (authorization, task) = notifications.recv().await.unwrap();
+    assert_eq!(authorization.as_deref(), Some("Bearer token"));
+    assert_eq!(task["uid"], 0);
+    assert_eq!(task["indexUid"], "test");
+    assert_eq!(task["type"], "indexCreation");
+    assert_eq!(task["status"], "succeeded");
+
+    // the canceled tasks are notified too.
+    let index = server.index("test");
+    for _ in 0..5 {
+        index
+            .add_documents(
+                serde_json::from_str(include_str!("../assets/test_set.json")).unwrap(),
+                None,
+            )
+            .await;
+    }
+    let (response, code) = index.cancel_task(5).await;
+    assert_eq!(code, 200, "{}", response);
+
+    loop {
+        let (_, task) = notifications.recv().await.unwrap();
+        if task["uid"] == 5 {
+            assert_eq!(task["status"], "canceled");
+            break;
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn settings_updates_are_batched() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.scheduler_options.enable_auto_batching = true;
+    // leaves the time to enqueue all the updates before the batch is prepared.
+    options.scheduler_options.debounce_duration_sec = Some(1);
+    let server = Server::new_with_options(options).await;
+    let index = server.index("test");
+
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre"] }))
+        .await;
+    index.wait_task(2).await;
+
+    let (first, _) = index.get_task(1).await;
+    let (second, _) = index.get_task(2).await;
+    assert_eq!(first["status"], "succeeded", "{}", first);
+    assert_eq!(second["status"], "succeeded", "{}", second);
+    assert_eq!(first["batchUid"], 1);
+    assert_eq!(second["batchUid"], 1);
+
+    let (settings, _) = index.settings().await;
+    assert_eq!(settings["searchableAttributes"], json!(["title"]));
+    assert_eq!(settings["filterableAttributes"], json!(["genre"]));
+}
+
+#[actix_rt::test]
+async fn conflicting_settings_updates_are_batched() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.scheduler_options.enable_auto_batching = true;
+    options.scheduler_options.debounce_duration_sec = Some(1);
+    let server = Server::new_with_options(options).await;
+    let index = server.index("test");
+
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    index
+        .update_settings(json!({
+            "searchableAttributes": ["title"],
+            "filterableAttributes": ["genre"],
+            "typoTolerance": { "minWordSizeForTypos": { "oneTypo": 3, "twoTypos": 6 } },
+        }))
+        .await;
+    index
+        .update_settings(json!({
+            "searchableAttributes": ["overview"],
+            "filterableAttributes": null,
+            "typoTolerance": { "minWordSizeForTypos": { "oneTypo": 4 } },
+        }))
+        .await;
+    index.wait_task(2).await;
+
+    let (first, _) = index.get_task(1).await;
+    let (second, _) = index.get_task(2).await;
+    assert_eq!(first["status"], "succeeded", "{}", first);
+    assert_eq!(second["status"], "succeeded", "{}", second);
+    assert_eq!(first["batchUid"], second["batchUid"]);
+
+    // the settings are applied as if the updates were processed one after the other.
+    let (settings, _) = index.settings().await;
+    assert_eq!(settings["searchableAttributes"], json!(["overview"]));
+    assert_eq!(settings["filterableAttributes"], json!([]));
+    assert_eq!(
+        settings["typoTolerance"]["minWordSizeForTypos"],
+        json!({ "oneTypo": 4, "twoTypos": 6 })
+    );
+}
+
+#[actix_rt::test]
+async fn task_priority() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/test/settings?priority=high",
+            json!({ "filterableAttributes": ["genre"] }),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(1).await;
+
+    let (response, _) = index.get_task(0).await;
+    assert_eq!(response["priority"], "normal");
+    let (response, _) = index.get_task(1).await;
+    assert_eq!(response["priority"], "high");
+    assert_eq!(response["status"], "succeeded");
+
+    let (response, code) = server
+        .service
+        .post("/indexes/test/settings?priority=urgent", json!({}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn concurrent_batch_workers() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.scheduler_options.batch_workers = Some(2);
+    let server = Server::new_with_options(options).await;
+
+    for uid in ["test1", "test2"] {
+        let index = server.index(uid);
+        index.add_documents(json!([{"id": 1}]), None).await;
+        index.add_documents(json!([{"id": 2}]), None).await;
+    }
+    server.index("test2").wait_task(3).await;
+    server.index("test1").wait_task(1).await;
+
+    for uid in ["test1", "test2"] {
+        let (response, code) = server.index(uid).list_tasks().await;
+        assert_eq!(code, 200, "{}", response);
+        for task in response["results"].as_array().unwrap() {
+            assert_eq!(task["status"], "succeeded", "{}", task);
+        }
+
+        let (response, code) = server.index(uid).stats().await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["numberOfDocuments"], 2);
+    }
+}
+
+#[actix_rt::test]
+async fn list_tasks_paginated() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    for id in 0..4 {
+        index.add_documents(json!([{ "id": id }]), None).await;
+    }
+    index.wait_task(4).await;
+
+    let (response, code) = server.tasks_filter("limit=2").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+    assert_eq!(response["limit"], 2);
+    assert_eq!(response["from"], 4);
+    assert_eq!(response["next"], 2);
+
+    let (response, code) = server.tasks_filter("limit=2&from=2").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["from"], 2);
+    assert_eq!(response["next"], 0);
+
+    let (response, code) = server.tasks_filter("limit=2&from=0").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["from"], 0);
+    assert_eq!(response["next"], json!(null));
+}
+
+#[actix_rt::test]
+async fn error_list_tasks_invalid_status() {
+    let server = Server::new().await;
+    let (response, code) = server.tasks_filter("status=done").await;
+
+    let expected_response = json!({
+        "message": "Task status `done` is invalid. Available task statuses are: `enqueued`, `processing`, `succeeded`, `failed`, `canceled`.",
+        "code": "bad_request",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#bad_request"
+    });
+
+    assert_eq!(response, expected_response);
+    assert_eq!(code, 400);
+}
+
 macro_rules! assert_valid_summarized_task {
     ($response:expr, $task_type:literal, $index:literal) => {{
         assert_eq!($response.as_object().unwrap().len(), 5);
@@ -4,6 +4,9 @@ use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, Write};
 
 use meilisearch_error::{internal_error, Code, ErrorCode};
 use milli::documents::DocumentBatchBuilder;
+use serde_json::Value;
+
+use crate::index::Document;
 
 type Result<T> = std::result::Result<T, DocumentFormatError>;
 
@@ -76,7 +79,7 @@ impl ErrorCode for DocumentFormatError {
     }
 }
 
-internal_error!(DocumentFormatError: io::Error);
+internal_error!(DocumentFormatError: io::Error, serde_json::Error, csv::Error);
 
 /// reads csv from input and write an obkv batch to writer.
 pub fn read_csv(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
@@ -126,3 +129,75 @@ pub fn read_json(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
 
     Ok(count)
 }
+
+/// writes the documents to writer as jsonl, one document per line.
+pub fn write_ndjson(documents: &[Document], mut writer: impl Write) -> Result<()> {
+    for document in documents {
+        serde_json::to_writer(&mut writer, document)?;
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+/// writes the csv header to writer, with one column per field.
+pub fn write_csv_header(fields: &[String], writer: impl Write) -> Result<()> {
+    // csv writes an empty record as `""`, so an index without fields gets no header at all.
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(fields)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// writes the documents to writer as csv rows, with one column per field. The strings are written
+/// as is, the missing and `null` fields are left empty and the other values are written as json.
+pub fn write_csv(fields: &[String], documents: &[Document], writer: impl Write) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for document in documents {
+        writer.write_record(fields.iter().map(|field| match document.get(field) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(value)) => value.clone(),
+            Some(value) => value.to_string(),
+        }))?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_write_documents() {
+        let documents: Vec<Document> = serde_json::from_value(json!([
+            { "id": 1, "title": "hello, \"world\"", "tags": ["a", "b"] },
+            { "id": 2, "title": null },
+        ]))
+        .unwrap();
+
+        let mut ndjson = Vec::new();
+        write_ndjson(&documents, &mut ndjson).unwrap();
+        assert_eq!(
+            String::from_utf8(ndjson).unwrap(),
+            "{\"id\":1,\"title\":\"hello, \\\"world\\\"\",\"tags\":[\"a\",\"b\"]}\n{\"id\":2,\"title\":null}\n"
+        );
+
+        let fields = vec!["id".to_string(), "title".to_string(), "tags".to_string()];
+        let mut csv = Vec::new();
+        write_csv_header(&fields, &mut csv).unwrap();
+        write_csv(&fields, &documents, &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "id,title,tags\n1,\"hello, \"\"world\"\"\",\"[\"\"a\"\",\"\"b\"\"]\"\n2,,\n"
+        );
+    }
+}
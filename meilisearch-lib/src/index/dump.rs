@@ -10,9 +10,10 @@ use milli::update::{IndexDocumentsConfig, IndexerConfig};
 use serde::{Deserialize, Serialize};
 
 use crate::document_formats::read_ndjson;
-use crate::index::updates::apply_settings_to_builder;
+use crate::index::updates::{apply_settings_to_builder, split_vectors, store_vectors};
 
 use super::error::Result;
+use super::vectors::{self, VECTORS_FIELD};
 use super::{index::Index, Settings, Unchecked};
 
 #[derive(Serialize, Deserialize)]
@@ -48,13 +49,16 @@ impl Index {
         // dump documents
         let mut json_map = IndexMap::new();
         for document in documents {
-            let (_, reader) = document?;
+            let (id, reader) = document?;
 
             for (fid, bytes) in reader.iter() {
                 if let Some(name) = fields_ids_map.name(fid) {
                     json_map.insert(name, serde_json::from_slice::<serde_json::Value>(bytes)?);
                 }
             }
+            if let Some(vectors) = vectors::get(self, txn, id)? {
+                json_map.insert(VECTORS_FIELD, vectors);
+            }
 
             serde_json::to_writer(&mut document_file, &json_map)?;
             document_file.write_all(b"\n")?;
@@ -112,8 +116,8 @@ impl Index {
         // Apply settings first
         let mut builder = milli::update::Settings::new(&mut txn, &index, indexer_config);
 
-        if let Some(primary_key) = primary_key {
-            builder.set_primary_key(primary_key);
+        if let Some(ref primary_key) = primary_key {
+            builder.set_primary_key(primary_key.clone());
         }
 
         apply_settings_to_builder(&settings, &mut builder);
@@ -136,6 +140,14 @@ impl Index {
             tmp_doc_file.seek(SeekFrom::Start(0))?;
 
             let documents_reader = DocumentBatchReader::from_reader(tmp_doc_file)?;
+            let mut primary_key = primary_key;
+            let mut pending_vectors = Vec::new();
+            let documents_reader = split_vectors(
+                documents_reader,
+                &mut primary_key,
+                false,
+                &mut pending_vectors,
+            )?;
 
             //If the document file is empty, we don't perform the document addition, to prevent
             //a primary key error to be thrown.
@@ -149,6 +161,7 @@ impl Index {
             )?;
             builder.add_documents(documents_reader)?;
             builder.execute()?;
+            store_vectors(&index, &mut txn, pending_vectors)?;
         }
 
         txn.commit()?;
@@ -13,6 +13,32 @@ pub enum IndexError {
     Internal(Box<dyn Error + Send + Sync + 'static>),
     #[error("Document `{0}` not found.")]
     DocumentNotFound(String),
+    #[error(
+        "The search cursor `{0}` is invalid or outdated. Restart the search without a cursor."
+    )]
+    InvalidSearchCursor(String),
+    #[error("A search cursor can't be used together with `attributesToSearchOn` or `rankingRules`, use `offset` instead.")]
+    SearchCursorWithOverrides,
+    #[error("Attribute `{0}` is not searchable. Only the searchable attributes of the index can be searched on.")]
+    InvalidAttributesToSearchOn(String),
+    #[error("The ranking rules of the search are invalid: {0}.")]
+    InvalidSearchRankingRules(String),
+    #[error("A search cursor can't be used together with a `vector`, use `offset` instead.")]
+    SearchCursorWithVector,
+    #[error(
+        "A `sort` can't be used together with a `vector`, the hybrid ranking doesn't follow it."
+    )]
+    SortWithVector,
+    #[error("`attributesToSearchOn` and `rankingRules` can't be used together with a `vector`, the hybrid ranking doesn't follow them.")]
+    SearchOverridesWithVector,
+    #[error("The documents cursor `{0}` is invalid.")]
+    InvalidDocumentsCursor(String),
+    #[error("The document edit on `{0}` is invalid: {1}.")]
+    InvalidDocumentEdit(String, String),
+    #[error("The semantic ratio must be between 0 and 1, found `{0}`.")]
+    InvalidSemanticRatio(f32),
+    #[error("None of the documents matching the search has a vector of dimension `{0}`.")]
+    InvalidVectorDimensions(usize),
     #[error("{0}")]
     Facet(#[from] FacetError),
     #[error("{0}")]
@@ -25,7 +51,8 @@ internal_error!(
     fst::Error,
     serde_json::Error,
     update_file_store::UpdateFileStoreError,
-    milli::documents::Error
+    milli::documents::Error,
+    grenad::Error<std::convert::Infallible>
 );
 
 impl ErrorCode for IndexError {
@@ -33,6 +60,17 @@ impl ErrorCode for IndexError {
         match self {
             IndexError::Internal(_) => Code::Internal,
             IndexError::DocumentNotFound(_) => Code::DocumentNotFound,
+            IndexError::InvalidSearchCursor(_)
+            | IndexError::SearchCursorWithOverrides
+            | IndexError::SearchCursorWithVector => Code::InvalidSearchCursor,
+            IndexError::InvalidAttributesToSearchOn(_) => Code::InvalidAttributesToSearchOn,
+            IndexError::InvalidSearchRankingRules(_) => Code::InvalidSearchRankingRules,
+            IndexError::InvalidDocumentsCursor(_) => Code::InvalidDocumentsCursor,
+            IndexError::InvalidDocumentEdit(..) => Code::InvalidDocumentEdit,
+            IndexError::SortWithVector => Code::Sort,
+            IndexError::SearchOverridesWithVector => Code::BadRequest,
+            IndexError::InvalidSemanticRatio(_) => Code::InvalidSemanticRatio,
+            IndexError::InvalidVectorDimensions(_) => Code::InvalidVectorDimensions,
             IndexError::Facet(e) => e.error_code(),
             IndexError::Milli(e) => MilliError(e).error_code(),
         }
@@ -43,12 +81,15 @@ impl ErrorCode for IndexError {
 pub enum FacetError {
     #[error("Invalid syntax for the filter parameter: `expected {}, found: {1}`.", .0.join(", "))]
     InvalidExpression(&'static [&'static str], Value),
+    #[error("Attribute `{0}` is not filterable. Add it to the filterable attributes to search its values.")]
+    AttributeNotFilterable(String),
 }
 
 impl ErrorCode for FacetError {
     fn error_code(&self) -> Code {
         match self {
             FacetError::InvalidExpression(_, _) => Code::Filter,
+            FacetError::AttributeNotFilterable(_) => Code::InvalidFacetName,
         }
     }
 }
@@ -1,3 +1,4 @@
+use std::cmp::min;
 use std::collections::{BTreeSet, HashSet};
 use std::fs::create_dir_all;
 use std::marker::PhantomData;
@@ -18,7 +19,9 @@ use crate::EnvSizer;
 
 use super::error::IndexError;
 use super::error::Result;
+use super::search::{expand_geo_bounding_boxes, parse_filter, HARD_RESULT_LIMIT};
 use super::updates::{MinWordSizeTyposSetting, TypoSettings};
+use super::vectors::{self, VECTORS_FIELD};
 use super::{Checked, Settings};
 
 pub type Document = Map<String, Value>;
@@ -64,6 +67,37 @@ pub struct IndexStats {
     pub field_distribution: FieldDistribution,
 }
 
+const DEFAULT_BROWSE_LIMIT: usize = 20;
+const fn default_browse_limit() -> usize {
+    DEFAULT_BROWSE_LIMIT
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BrowseQuery {
+    pub filter: Option<Value>,
+    /// The fields to return, that can point inside the nested objects, e.g. `doggos.name`.
+    pub fields: Option<Vec<String>>,
+    #[serde(default = "default_browse_limit")]
+    pub limit: usize,
+    /// Resumes the browsing after the last document of a previous page.
+    ///
+    /// The cursor is the internal id of that document. Milli reuses the ids of the deleted
+    /// documents and gives a new id to a document that is deleted and added again, so the
+    /// documents written between two pages can be skipped or returned twice.
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseResult {
+    pub results: Vec<Document>,
+    /// The number of documents matching the filter.
+    pub total: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Clone, derivative::Derivative)]
 #[derivative(Debug)]
 pub struct Index {
@@ -218,11 +252,14 @@ impl Index {
         })
     }
 
+    /// Returns the documents in the order of their internal ids, with their vectors under
+    /// `_vectors` when `retrieve_vectors` is set.
     pub fn retrieve_documents<S: AsRef<str>>(
         &self,
         offset: usize,
         limit: usize,
         attributes_to_retrieve: Option<Vec<S>>,
+        retrieve_vectors: bool,
     ) -> Result<Vec<Map<String, Value>>> {
         let txn = self.read_txn()?;
 
@@ -235,14 +272,78 @@ impl Index {
         let mut documents = Vec::new();
 
         for entry in iter {
-            let (_id, obkv) = entry?;
-            let object = obkv_to_json(&fields_to_display, &fields_ids_map, obkv)?;
+            let (id, obkv) = entry?;
+            let mut object = obkv_to_json(&fields_to_display, &fields_ids_map, obkv)?;
+            if retrieve_vectors {
+                if let Some(vectors) = vectors::get(self, &txn, id.get())? {
+                    object.insert(VECTORS_FIELD.to_string(), vectors);
+                }
+            }
             documents.push(object);
         }
 
         Ok(documents)
     }
 
+    /// Returns the documents matching the filter of the query, in the order of their internal ids.
+    pub fn browse_documents(&self, query: BrowseQuery) -> Result<BrowseResult> {
+        let txn = self.read_txn()?;
+
+        // The cursor is the internal id of the last document of the previous page.
+        let after = match query.cursor {
+            Some(ref cursor) => Some(
+                u32::from_str_radix(cursor, 16)
+                    .map_err(|_| IndexError::InvalidDocumentsCursor(cursor.clone()))?,
+            ),
+            None => None,
+        };
+
+        let filter = query.filter.as_ref().map(expand_geo_bounding_boxes);
+        let mut candidates = match filter.as_ref().map(parse_filter).transpose()?.flatten() {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => self.documents_ids(&txn)?,
+        };
+        let total = candidates.len();
+
+        // Removing the range keeps the cost of a page independent of its position.
+        if let Some(after) = after {
+            candidates.remove_range(..=after);
+        }
+
+        // A page is bounded like the hits of a search, the following ones are reached with the cursor.
+        let limit = min(query.limit, HARD_RESULT_LIMIT);
+        let mut ids: Vec<_> = candidates.iter().take(limit + 1).collect();
+        let next_cursor = if ids.len() > limit {
+            ids.truncate(limit);
+            ids.last().map(|id| format!("{:08x}", id))
+        } else {
+            None
+        };
+
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let fields_to_display =
+            self.fields_to_display(&txn, &None::<Vec<String>>, &fields_ids_map)?;
+
+        let mut results = Vec::with_capacity(ids.len());
+        for (_id, obkv) in self.documents(&txn, ids)? {
+            let document = obkv_to_json(&fields_to_display, &fields_ids_map, obkv)?;
+            let document = match query.fields {
+                Some(ref fields) => permissive_json_pointer::select_values(
+                    &document,
+                    fields.iter().map(String::as_str),
+                ),
+                None => document,
+            };
+            results.push(document);
+        }
+
+        Ok(BrowseResult {
+            results,
+            total,
+            next_cursor,
+        })
+    }
+
     pub fn retrieve_document<S: AsRef<str>>(
         &self,
         doc_id: String,
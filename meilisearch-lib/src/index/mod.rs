@@ -1,19 +1,23 @@
 pub use search::{
     default_crop_length, default_crop_marker, default_highlight_post_tag,
-    default_highlight_pre_tag, SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
-    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
+    default_highlight_pre_tag, FacetHit, FacetSearchQuery, FacetSearchResult, FacetStats,
+    MatchingStrategy, SearchHit, SearchQuery, SearchResult, DEFAULT_CROP_LENGTH,
+    DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+    DEFAULT_SEARCH_LIMIT, DEFAULT_SEMANTIC_RATIO,
 };
-pub use updates::{apply_settings_to_builder, Checked, Facets, Settings, Unchecked};
+pub use updates::{apply_settings_to_builder, Checked, DocumentEdit, Facets, Settings, Unchecked};
 
 mod dump;
 pub mod error;
+mod ranking;
 mod search;
 pub mod updates;
+mod vectors;
 
 #[allow(clippy::module_inception)]
 mod index;
 
-pub use index::{Document, IndexMeta, IndexStats};
+pub use index::{BrowseQuery, BrowseResult, Document, IndexMeta, IndexStats};
 
 #[cfg(not(test))]
 pub use index::Index;
@@ -37,7 +41,10 @@ pub mod test {
 
     use super::error::Result;
     use super::index::Index;
-    use super::{Checked, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings};
+    use super::{
+        BrowseQuery, BrowseResult, Checked, DocumentEdit, FacetSearchQuery, FacetSearchResult,
+        IndexMeta, IndexStats, SearchQuery, SearchResult, Settings,
+    };
     use crate::update_file_store::UpdateFileStore;
 
     #[derive(Clone)]
@@ -102,15 +109,26 @@ pub mod test {
             offset: usize,
             limit: usize,
             attributes_to_retrieve: Option<Vec<S>>,
+            retrieve_vectors: bool,
         ) -> Result<Vec<Map<String, Value>>> {
             match self {
-                MockIndex::Real(index) => {
-                    index.retrieve_documents(offset, limit, attributes_to_retrieve)
-                }
+                MockIndex::Real(index) => index.retrieve_documents(
+                    offset,
+                    limit,
+                    attributes_to_retrieve,
+                    retrieve_vectors,
+                ),
                 MockIndex::Mock(_) => todo!(),
             }
         }
 
+        pub fn browse_documents(&self, query: BrowseQuery) -> Result<BrowseResult> {
+            match self {
+                MockIndex::Real(index) => index.browse_documents(query),
+                MockIndex::Mock(m) => unsafe { m.get("browse_documents").call(query) },
+            }
+        }
+
         pub fn retrieve_document<S: AsRef<str>>(
             &self,
             doc_id: String,
@@ -150,6 +168,13 @@ pub mod test {
             }
         }
 
+        pub fn perform_facet_search(&self, query: FacetSearchQuery) -> Result<FacetSearchResult> {
+            match self {
+                MockIndex::Real(index) => index.perform_facet_search(query),
+                MockIndex::Mock(m) => unsafe { m.get("perform_facet_search").call(query) },
+            }
+        }
+
         pub fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.dump(path),
@@ -176,6 +201,22 @@ pub mod test {
             }
         }
 
+        pub fn merge_documents(
+            &self,
+            primary_key: Option<String>,
+            file_store: UpdateFileStore,
+            contents: impl Iterator<Item = Uuid>,
+        ) -> Result<DocumentAdditionResult> {
+            match self {
+                MockIndex::Real(index) => index.merge_documents(primary_key, file_store, contents),
+                MockIndex::Mock(mocker) => unsafe {
+                    mocker
+                        .get("merge_documents")
+                        .call((primary_key, file_store, contents))
+                },
+            }
+        }
+
         pub fn update_settings(&self, settings: &Settings<Checked>) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.update_settings(settings),
@@ -197,6 +238,24 @@ pub mod test {
             }
         }
 
+        pub fn delete_documents_by_filter(&self, filter: &Value) -> Result<DocumentDeletionResult> {
+            match self {
+                MockIndex::Real(index) => index.delete_documents_by_filter(filter),
+                MockIndex::Mock(m) => unsafe { m.get("delete_documents_by_filter").call(filter) },
+            }
+        }
+
+        pub fn edit_documents(
+            &self,
+            filter: Option<&Value>,
+            edits: &[DocumentEdit],
+        ) -> Result<DocumentAdditionResult> {
+            match self {
+                MockIndex::Real(index) => index.edit_documents(filter, edits),
+                MockIndex::Mock(m) => unsafe { m.get("edit_documents").call((filter, edits)) },
+            }
+        }
+
         pub fn clear_documents(&self) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.clear_documents(),
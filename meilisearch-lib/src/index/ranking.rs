@@ -0,0 +1,821 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use milli::heed::RoTxn;
+use milli::tokenizer::{Analyzer, AnalyzerConfig};
+use milli::{AscDesc, Criterion, DocumentId, Member};
+use serde_json::{json, Map, Value};
+
+use super::error::{IndexError, Result};
+use super::index::Index;
+use super::search::Document;
+
+/// The distance given to two query words that are not found close to each other.
+const MAX_PROXIMITY: usize = 8;
+
+/// The gap between the positions of two values of an array, so that they are never close.
+const ARRAY_VALUES_GAP: usize = MAX_PROXIMITY;
+
+/// The ranking rules and the searched attributes of a search, when they differ from the index
+/// settings. milli reads both from the settings, so the hits ranked by milli are ranked again.
+pub struct RankingOverrides {
+    rules: Vec<Criterion>,
+    /// The searched attributes, in the order of the searchable attributes of the index.
+    attributes: Vec<String>,
+    /// Whether only a part of the searchable attributes is searched.
+    restricted: bool,
+}
+
+impl RankingOverrides {
+    /// Checks the overrides against the settings of the index, returns `None` when there is none.
+    pub fn new(
+        index: &Index,
+        rtxn: &RoTxn,
+        attributes_to_search_on: Option<&[String]>,
+        ranking_rules: Option<&[String]>,
+    ) -> Result<Option<Self>> {
+        if attributes_to_search_on.is_none() && ranking_rules.is_none() {
+            return Ok(None);
+        }
+
+        let searchable = searchable_attributes(index, rtxn)?;
+
+        let (attributes, restricted) = match attributes_to_search_on {
+            Some(attributes) => {
+                if let Some(attribute) = attributes.iter().find(|a| !searchable.contains(a)) {
+                    return Err(IndexError::InvalidAttributesToSearchOn(attribute.clone()));
+                }
+                let subset: Vec<_> = searchable
+                    .iter()
+                    .filter(|attribute| attributes.contains(attribute))
+                    .cloned()
+                    .collect();
+                let restricted = subset.len() < searchable.len();
+                (subset, restricted)
+            }
+            None => (searchable, false),
+        };
+
+        let index_rules = index.criteria(rtxn)?;
+        let rules = match ranking_rules {
+            Some(rules) => {
+                let rules = rules
+                    .iter()
+                    .map(|rule| Criterion::from_str(rule))
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|error| IndexError::InvalidSearchRankingRules(error.to_string()))?;
+
+                let mut sorted: Vec<_> = rules.iter().map(ToString::to_string).collect();
+                let mut index_sorted: Vec<_> =
+                    index_rules.iter().map(ToString::to_string).collect();
+                sorted.sort_unstable();
+                index_sorted.sort_unstable();
+                if sorted != index_sorted {
+                    return Err(IndexError::InvalidSearchRankingRules(format!(
+                        "they must be a reordering of the ranking rules of the index `{:?}`",
+                        index_rules
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                    )));
+                }
+                rules
+            }
+            None => index_rules,
+        };
+
+        Ok(Some(Self {
+            rules,
+            attributes,
+            restricted,
+        }))
+    }
+
+    /// Whether the overrides leave out some of the hits found by milli.
+    pub fn restricted(&self) -> bool {
+        self.restricted
+    }
+
+    /// Ranks the documents again with the overridden rules, the documents keep the order of
+    /// milli between them when the rules can't tell them apart. When only a part of the
+    /// searchable attributes is searched, the documents not matching the query in these
+    /// attributes are left out.
+    pub fn rerank(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        query: Option<&str>,
+        sort: &[AscDesc],
+        documents_ids: &[DocumentId],
+    ) -> Result<Vec<DocumentId>> {
+        let stop_words = fst::Set::default();
+        let mut config = AnalyzerConfig::default();
+        config.stop_words(&stop_words);
+        let analyzer = Analyzer::new(config);
+        let query = QueryWords::new(index, rtxn, &analyzer, query.unwrap_or_default())?;
+
+        let fields_ids_map = index.fields_ids_map(rtxn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+        let mut hits = Vec::with_capacity(documents_ids.len());
+        for (id, obkv) in index.documents(rtxn, documents_ids.iter().copied())? {
+            let document = milli::obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+            let ranks = query.ranks(&analyzer, &document, &self.attributes);
+            if self.is_left_out(&query, &ranks) {
+                continue;
+            }
+            hits.push((id, ranks, document));
+        }
+
+        hits.sort_by(|(_, a_ranks, a), (_, b_ranks, b)| {
+            self.rules
+                .iter()
+                .map(|rule| compare(rule, sort, (a_ranks, a), (b_ranks, b)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        Ok(hits.into_iter().map(|(id, _, _)| id).collect())
+    }
+
+    /// Returns the documents matching the query in the searched attributes, in the same order.
+    pub fn matching(
+        &self,
+        index: &Index,
+        rtxn: &RoTxn,
+        query: Option<&str>,
+        documents_ids: &[DocumentId],
+    ) -> Result<Vec<DocumentId>> {
+        // The documents are fetched by chunks to bound the memory used by large result sets.
+        const CHUNK_SIZE: usize = 1000;
+
+        let stop_words = fst::Set::default();
+        let mut config = AnalyzerConfig::default();
+        config.stop_words(&stop_words);
+        let analyzer = Analyzer::new(config);
+        let query = QueryWords::new(index, rtxn, &analyzer, query.unwrap_or_default())?;
+
+        let fields_ids_map = index.fields_ids_map(rtxn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+        let mut matching = Vec::new();
+        for chunk in documents_ids.chunks(CHUNK_SIZE) {
+            for (id, obkv) in index.documents(rtxn, chunk.iter().copied())? {
+                let document = milli::obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+                let ranks = query.ranks(&analyzer, &document, &self.attributes);
+                if !self.is_left_out(&query, &ranks) {
+                    matching.push(id);
+                }
+            }
+        }
+
+        Ok(matching)
+    }
+
+    fn is_left_out(&self, query: &QueryWords, ranks: &HitRanks) -> bool {
+        self.restricted && !query.is_empty() && ranks.words == 0
+    }
+}
+
+/// The searchable attributes of the index, in their order of importance.
+fn searchable_attributes(index: &Index, rtxn: &RoTxn) -> Result<Vec<String>> {
+    Ok(match index.searchable_fields(rtxn)? {
+        Some(fields) => fields.into_iter().map(String::from).collect(),
+        None => index
+            .fields_ids_map(rtxn)?
+            .iter()
+            .map(|(_, name)| name.to_string())
+            .collect(),
+    })
+}
+
+/// The relevancy of a hit: a score between 0 and 1, and the details of each ranking rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankingScore {
+    pub score: f64,
+    pub details: Map<String, Value>,
+}
+
+/// Computes the ranking score of the documents with the ranking rules and the searched attributes
+/// of the search. The ranks are found the same way the overrides rank the hits again, they can
+/// then slightly differ from the buckets milli put a hit in.
+pub fn ranking_scores(
+    index: &Index,
+    rtxn: &RoTxn,
+    overrides: Option<&RankingOverrides>,
+    query: Option<&str>,
+    sort: &[AscDesc],
+    documents_ids: &[DocumentId],
+) -> Result<HashMap<DocumentId, RankingScore>> {
+    let (rules, attributes) = match overrides {
+        Some(overrides) => (overrides.rules.clone(), overrides.attributes.clone()),
+        None => (index.criteria(rtxn)?, searchable_attributes(index, rtxn)?),
+    };
+
+    let stop_words = fst::Set::default();
+    let mut config = AnalyzerConfig::default();
+    config.stop_words(&stop_words);
+    let analyzer = Analyzer::new(config);
+    let query = QueryWords::new(index, rtxn, &analyzer, query.unwrap_or_default())?;
+
+    let fields_ids_map = index.fields_ids_map(rtxn)?;
+    let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+    let mut scores = HashMap::new();
+    for (id, obkv) in index.documents(rtxn, documents_ids.iter().copied())? {
+        let document = milli::obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+        let ranks = query.ranks(&analyzer, &document, &attributes);
+        let score = query.score(&rules, sort, attributes.len(), &ranks, &document);
+        scores.insert(id, score);
+    }
+
+    Ok(scores)
+}
+
+/// Where the query words are found in a hit, for each ranking rule.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HitRanks {
+    /// The number of query words found.
+    pub words: usize,
+    /// The number of typos of the query words found.
+    pub typos: usize,
+    /// The sum of the distances between the consecutive query words found.
+    pub proximity: usize,
+    /// The index of the first attribute containing a query word, and the position of the word.
+    pub attribute: Option<(usize, usize)>,
+    /// Whether an attribute is exactly the query.
+    pub exact_attribute: bool,
+    /// The number of query words found as they are, without typo and not as a prefix.
+    pub exact_words: usize,
+}
+
+struct QueryWord {
+    text: String,
+    chars: Vec<char>,
+    /// The last word of the query also matches the words it starts.
+    prefix: bool,
+    allowed_typos: usize,
+}
+
+impl QueryWord {
+    /// Returns the number of typos of the word when it matches the token, and whether it matches
+    /// exactly.
+    fn matches(&self, token: &str) -> Option<(usize, bool)> {
+        if token == self.text {
+            return Some((0, true));
+        }
+
+        let token: Vec<char> = token.chars().collect();
+        if self.prefix && token.starts_with(&self.chars) {
+            return Some((0, false));
+        }
+
+        let typos = edit_distance(&self.chars, &token, self.prefix);
+        (typos <= self.allowed_typos).then(|| (typos, false))
+    }
+}
+
+/// The words of a query, matched against the words of the documents as milli does.
+pub struct QueryWords {
+    words: Vec<QueryWord>,
+}
+
+impl QueryWords {
+    pub fn new<A: AsRef<[u8]>>(
+        index: &Index,
+        rtxn: &RoTxn,
+        analyzer: &Analyzer<A>,
+        query: &str,
+    ) -> Result<Self> {
+        let authorize_typos = index.authorize_typos(rtxn)?;
+        let one_typo = index.min_word_len_one_typo(rtxn)? as usize;
+        let two_typos = index.min_word_len_two_typos(rtxn)? as usize;
+
+        let analyzed = analyzer.analyze(query);
+        let texts: Vec<String> = analyzed
+            .tokens()
+            .filter(|token| token.is_word())
+            .map(|token| token.text().to_string())
+            .collect();
+        // the last word is a prefix while the user is still typing it.
+        let ends_with_word = query.chars().last().map_or(false, char::is_alphanumeric);
+
+        let count = texts.len();
+        let words = texts
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let chars: Vec<char> = text.chars().collect();
+                let allowed_typos = match chars.len() {
+                    _ if !authorize_typos => 0,
+                    len if len >= two_typos => 2,
+                    len if len >= one_typo => 1,
+                    _ => 0,
+                };
+                QueryWord {
+                    text,
+                    chars,
+                    prefix: i + 1 == count && ends_with_word,
+                    allowed_typos,
+                }
+            })
+            .collect();
+
+        Ok(Self { words })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Scores the ranks of a hit on each ranking rule. Like the ranking rules, the score is
+    /// lexicographic: a rule only tells apart the hits that are tied on the rules before it. The
+    /// sort rules are detailed with the value the hit is sorted on, and are left out of the score.
+    fn score(
+        &self,
+        rules: &[Criterion],
+        sort: &[AscDesc],
+        attributes_count: usize,
+        ranks: &HitRanks,
+        document: &Document,
+    ) -> RankingScore {
+        let count = self.words.len();
+        let max_typos = self.words.iter().map(|word| word.allowed_typos).sum();
+
+        let mut details = Map::new();
+        // The position of the hit among all the possible ranks of the rules seen so far, between
+        // 0 for the best hit and 1 excluded.
+        let mut position = 0.;
+        let mut width = 1.;
+        let mut push_rank = |details: &mut Map<String, Value>,
+                             name: &str,
+                             rank: usize,
+                             max: usize,
+                             mut detail: Value| {
+            let score = if max == 0 {
+                1.
+            } else {
+                1. - rank.min(max) as f64 / max as f64
+            };
+            width /= (max + 1) as f64;
+            position += rank.min(max) as f64 * width;
+
+            detail["order"] = json!(details.len());
+            detail["score"] = json!(score);
+            details.insert(name.to_string(), detail);
+        };
+
+        for rule in rules {
+            match rule {
+                // the rules on the query words don't tell apart the hits of a placeholder search.
+                Criterion::Words
+                | Criterion::Typo
+                | Criterion::Proximity
+                | Criterion::Attribute
+                | Criterion::Exactness
+                    if self.is_empty() => {}
+                Criterion::Words => push_rank(
+                    &mut details,
+                    "words",
+                    count - ranks.words,
+                    count,
+                    json!({ "matchingWords": ranks.words, "maxMatchingWords": count }),
+                ),
+                Criterion::Typo => push_rank(
+                    &mut details,
+                    "typo",
+                    ranks.typos,
+                    max_typos,
+                    json!({ "typoCount": ranks.typos, "maxTypoCount": max_typos }),
+                ),
+                Criterion::Proximity => {
+                    // consecutive words are at a distance of one.
+                    let pairs = ranks.words.saturating_sub(1);
+                    push_rank(
+                        &mut details,
+                        "proximity",
+                        ranks.proximity.saturating_sub(pairs),
+                        count.saturating_sub(1) * (MAX_PROXIMITY - 1),
+                        json!({}),
+                    )
+                }
+                Criterion::Attribute => push_rank(
+                    &mut details,
+                    "attribute",
+                    ranks
+                        .attribute
+                        .map_or(attributes_count, |(attribute, _)| attribute),
+                    attributes_count,
+                    json!({}),
+                ),
+                Criterion::Exactness => {
+                    let (rank, match_type) = if ranks.exact_attribute {
+                        (0, "exactMatch")
+                    } else {
+                        (1 + count - ranks.exact_words, "noExactMatch")
+                    };
+                    push_rank(
+                        &mut details,
+                        "exactness",
+                        rank,
+                        count + 1,
+                        json!({ "matchType": match_type, "exactWords": ranks.exact_words }),
+                    )
+                }
+                Criterion::Sort => {
+                    for asc_desc in sort {
+                        insert_sort_detail(&mut details, asc_desc, document);
+                    }
+                }
+                Criterion::Asc(field) => insert_sort_detail(
+                    &mut details,
+                    &AscDesc::Asc(Member::Field(field.clone())),
+                    document,
+                ),
+                Criterion::Desc(field) => insert_sort_detail(
+                    &mut details,
+                    &AscDesc::Desc(Member::Field(field.clone())),
+                    document,
+                ),
+            }
+        }
+
+        let score = if width < 1. {
+            1. - position / (1. - width)
+        } else {
+            1.
+        };
+
+        RankingScore { score, details }
+    }
+
+    /// Finds the query words in the attributes of the document.
+    pub fn ranks<A: AsRef<[u8]>>(
+        &self,
+        analyzer: &Analyzer<A>,
+        document: &Document,
+        attributes: &[String],
+    ) -> HitRanks {
+        // The best match of each query word: its typos, whether it is exact, and its attribute and
+        // position.
+        let mut best: Vec<Option<(usize, bool, usize, usize)>> = vec![None; self.words.len()];
+        let mut positions: Vec<Vec<(usize, usize)>> = vec![Vec::new(); self.words.len()];
+        let mut exact_attribute = false;
+
+        for (attribute_index, attribute) in attributes.iter().enumerate() {
+            let values = permissive_json_pointer::select_values(document, Some(attribute.as_str()));
+            let mut tokens = Vec::new();
+            collect_words(analyzer, &Value::Object(values), &mut tokens, &mut 0);
+
+            exact_attribute |= !tokens.is_empty()
+                && tokens.len() == self.words.len()
+                && tokens
+                    .iter()
+                    .zip(&self.words)
+                    .all(|((_, token), word)| *token == word.text);
+
+            for (position, token) in &tokens {
+                for (i, word) in self.words.iter().enumerate() {
+                    if let Some((typos, exact)) = word.matches(token) {
+                        positions[i].push((attribute_index, *position));
+                        let candidate = (typos, exact, attribute_index, *position);
+                        let better = match best[i] {
+                            Some((best_typos, best_exact, ..)) => {
+                                (typos, !exact) < (best_typos, !best_exact)
+                            }
+                            None => true,
+                        };
+                        if better {
+                            best[i] = Some(candidate);
+                        }
+                    }
+                }
+            }
+        }
+
+        let found: Vec<_> = best
+            .iter()
+            .enumerate()
+            .filter_map(|(i, best)| best.map(|best| (i, best)))
+            .collect();
+
+        let proximity = found
+            .windows(2)
+            .map(|pair| {
+                let (left, right) = (&positions[pair[0].0], &positions[pair[1].0]);
+                left.iter()
+                    .flat_map(|&(left_attribute, left_position)| {
+                        right
+                            .iter()
+                            .filter(move |&&(attribute, _)| attribute == left_attribute)
+                            .map(move |&(_, position)| {
+                                if position > left_position {
+                                    position - left_position
+                                } else {
+                                    left_position - position + 1
+                                }
+                            })
+                    })
+                    .min()
+                    .unwrap_or(MAX_PROXIMITY)
+                    .min(MAX_PROXIMITY)
+            })
+            .sum();
+
+        HitRanks {
+            words: found.len(),
+            typos: found.iter().map(|(_, (typos, ..))| typos).sum(),
+            proximity,
+            attribute: found
+                .iter()
+                .map(|&(_, (_, _, attribute, position))| (attribute, position))
+                .min(),
+            exact_attribute,
+            exact_words: found.iter().filter(|(_, (_, exact, ..))| *exact).count(),
+        }
+    }
+}
+
+/// Collects the words of a value with their positions.
+fn collect_words<A: AsRef<[u8]>>(
+    analyzer: &Analyzer<A>,
+    value: &Value,
+    words: &mut Vec<(usize, String)>,
+    position: &mut usize,
+) {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        Value::Bool(boolean) => boolean.to_string(),
+        Value::Array(values) => {
+            for value in values {
+                collect_words(analyzer, value, words, position);
+                *position += ARRAY_VALUES_GAP;
+            }
+            return;
+        }
+        Value::Object(object) => {
+            for value in object.values() {
+                collect_words(analyzer, value, words, position);
+                *position += ARRAY_VALUES_GAP;
+            }
+            return;
+        }
+        Value::Null => return,
+    };
+
+    let analyzed = analyzer.analyze(&text);
+    for token in analyzed.tokens().filter(|token| token.is_word()) {
+        words.push((*position, token.text().to_string()));
+        *position += 1;
+    }
+}
+
+/// Computes the edit distance between the word and the token, or the smallest one between the
+/// word and a prefix of the token.
+fn edit_distance(word: &[char], token: &[char], prefix: bool) -> usize {
+    // `previous[j]` is the distance between the word processed so far and `token[..j]`.
+    let mut previous: Vec<usize> = (0..=token.len()).collect();
+    for (i, word_char) in word.iter().enumerate() {
+        let mut current = vec![i + 1; token.len() + 1];
+        for (j, token_char) in token.iter().enumerate() {
+            let substitution = previous[j] + (word_char != token_char) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    if prefix {
+        previous.into_iter().min().unwrap_or_default()
+    } else {
+        previous[token.len()]
+    }
+}
+
+/// Orders two hits on a ranking rule, the best hit first.
+fn compare(
+    rule: &Criterion,
+    sort: &[AscDesc],
+    (a_ranks, a): (&HitRanks, &Document),
+    (b_ranks, b): (&HitRanks, &Document),
+) -> Ordering {
+    match rule {
+        Criterion::Words => b_ranks.words.cmp(&a_ranks.words),
+        Criterion::Typo => a_ranks.typos.cmp(&b_ranks.typos),
+        Criterion::Proximity => a_ranks.proximity.cmp(&b_ranks.proximity),
+        Criterion::Attribute => match (a_ranks.attribute, b_ranks.attribute) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        },
+        Criterion::Exactness => (b_ranks.exact_attribute, b_ranks.exact_words)
+            .cmp(&(a_ranks.exact_attribute, a_ranks.exact_words)),
+        Criterion::Sort => sort
+            .iter()
+            .map(|asc_desc| compare_asc_desc(asc_desc, a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal),
+        Criterion::Asc(field) => {
+            compare_asc_desc(&AscDesc::Asc(Member::Field(field.clone())), a, b)
+        }
+        Criterion::Desc(field) => {
+            compare_asc_desc(&AscDesc::Desc(Member::Field(field.clone())), a, b)
+        }
+    }
+}
+
+/// A value a document is sorted on.
+#[derive(Debug, PartialEq, PartialOrd)]
+enum SortValue {
+    Number(f64),
+    String(String),
+}
+
+/// Details the value a document is sorted on.
+fn insert_sort_detail(details: &mut Map<String, Value>, asc_desc: &AscDesc, document: &Document) {
+    let (member, direction) = match asc_desc {
+        AscDesc::Asc(member) => (member, "asc"),
+        AscDesc::Desc(member) => (member, "desc"),
+    };
+    let order = details.len();
+    let (name, detail) = match member {
+        Member::Field(field) => {
+            let value = match sort_value(asc_desc, document) {
+                Some(SortValue::Number(number)) => json!(number),
+                Some(SortValue::String(string)) => json!(string),
+                None => Value::Null,
+            };
+            (
+                format!("{}:{}", field, direction),
+                json!({ "order": order, "value": value }),
+            )
+        }
+        Member::Geo([lat, lng]) => {
+            let distance = match sort_value(asc_desc, document) {
+                Some(SortValue::Number(distance)) => json!(distance.round()),
+                _ => Value::Null,
+            };
+            (
+                format!("_geoPoint({}, {}):{}", lat, lng, direction),
+                json!({ "order": order, "distance": distance }),
+            )
+        }
+    };
+    details.insert(name, detail);
+}
+
+/// Returns the value a document is sorted on by a sort rule.
+fn sort_value(asc_desc: &AscDesc, document: &Document) -> Option<SortValue> {
+    match asc_desc {
+        AscDesc::Asc(Member::Field(field)) | AscDesc::Desc(Member::Field(field)) => {
+            let mut values = Vec::new();
+            let selected = permissive_json_pointer::select_values(document, Some(field.as_str()));
+            collect_sort_values(&Value::Object(selected), &mut values);
+            // like milli, the smallest value of an array is used to sort it in the ascending
+            // order, and its largest value in the descending order.
+            let best = |a: &SortValue, b: &SortValue| a.partial_cmp(b).unwrap_or(Ordering::Equal);
+            match asc_desc {
+                AscDesc::Asc(_) => values.into_iter().min_by(best),
+                AscDesc::Desc(_) => values.into_iter().max_by(best),
+            }
+        }
+        AscDesc::Asc(Member::Geo(point)) | AscDesc::Desc(Member::Geo(point)) => {
+            let geo = document.get("_geo")?;
+            let lat = geo.get("lat")?.as_f64()?;
+            let lng = geo.get("lng")?.as_f64()?;
+            Some(SortValue::Number(milli::distance_between_two_points(
+                point,
+                &[lat, lng],
+            )))
+        }
+    }
+}
+
+/// Orders two documents on a sort rule, the documents without value last.
+fn compare_asc_desc(asc_desc: &AscDesc, a: &Document, b: &Document) -> Ordering {
+    let ascending = matches!(asc_desc, AscDesc::Asc(_));
+
+    match (sort_value(asc_desc, a), sort_value(asc_desc, b)) {
+        (Some(a), Some(b)) => {
+            let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn collect_sort_values(value: &Value, values: &mut Vec<SortValue>) {
+    match value {
+        Value::Number(number) => values.extend(number.as_f64().map(SortValue::Number)),
+        Value::String(string) => values.push(SortValue::String(string.to_lowercase())),
+        Value::Array(array) => array
+            .iter()
+            .for_each(|value| collect_sort_values(value, values)),
+        Value::Object(object) => object
+            .values()
+            .for_each(|value| collect_sort_values(value, values)),
+        Value::Bool(_) | Value::Null => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("hello"), &chars("hello"), false), 0);
+        assert_eq!(edit_distance(&chars("helo"), &chars("hello"), false), 1);
+        assert_eq!(edit_distance(&chars("hel"), &chars("hello"), false), 2);
+        assert_eq!(edit_distance(&chars("hel"), &chars("hello"), true), 0);
+        assert_eq!(edit_distance(&chars("hwl"), &chars("hello"), true), 1);
+    }
+
+    #[test]
+    fn test_ranking_score() {
+        let word = |text: &str| QueryWord {
+            text: text.to_string(),
+            chars: text.chars().collect(),
+            prefix: false,
+            allowed_typos: 1,
+        };
+        let query = QueryWords {
+            words: vec![word("hello"), word("world")],
+        };
+        let rules = [
+            Criterion::Words,
+            Criterion::Typo,
+            Criterion::Asc("price".to_string()),
+        ];
+        let document = match serde_json::json!({ "price": 10 }) {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        };
+
+        let best = HitRanks {
+            words: 2,
+            ..Default::default()
+        };
+        let score = query.score(&rules, &[], 1, &best, &document);
+        assert_eq!(score.score, 1.);
+        assert_eq!(
+            Value::Object(score.details),
+            serde_json::json!({
+                "words": { "order": 0, "matchingWords": 2, "maxMatchingWords": 2, "score": 1.0 },
+                "typo": { "order": 1, "typoCount": 0, "maxTypoCount": 2, "score": 1.0 },
+                "price:asc": { "order": 2, "value": 10.0 },
+            })
+        );
+
+        // a typo weighs less than a missing word.
+        let typo = HitRanks {
+            words: 2,
+            typos: 2,
+            ..Default::default()
+        };
+        let missing_word = HitRanks {
+            words: 1,
+            ..Default::default()
+        };
+        let typo = query.score(&rules, &[], 1, &typo, &document).score;
+        let missing_word = query.score(&rules, &[], 1, &missing_word, &document).score;
+        assert!(0. <= missing_word && missing_word < typo && typo < 1.);
+
+        // the query rules are left out of a placeholder search.
+        let placeholder = QueryWords { words: Vec::new() };
+        let score = placeholder.score(&rules, &[], 1, &HitRanks::default(), &document);
+        assert_eq!(score.score, 1.);
+        assert_eq!(score.details.keys().collect::<Vec<_>>(), ["price:asc"]);
+    }
+
+    #[test]
+    fn test_compare_asc_desc() {
+        let document = |value: Value| match value {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        };
+        let a = document(serde_json::json!({ "price": 10, "tags": [3, 1] }));
+        let b = document(serde_json::json!({ "price": 20, "tags": [2] }));
+        let c = document(serde_json::json!({}));
+
+        let asc = AscDesc::from_str("price:asc").unwrap();
+        let desc = AscDesc::from_str("price:desc").unwrap();
+        assert_eq!(compare_asc_desc(&asc, &a, &b), Ordering::Less);
+        assert_eq!(compare_asc_desc(&desc, &a, &b), Ordering::Greater);
+        // the documents without value are always last.
+        assert_eq!(compare_asc_desc(&asc, &a, &c), Ordering::Less);
+        assert_eq!(compare_asc_desc(&desc, &a, &c), Ordering::Less);
+
+        let tags_asc = AscDesc::from_str("tags:asc").unwrap();
+        let tags_desc = AscDesc::from_str("tags:desc").unwrap();
+        assert_eq!(compare_asc_desc(&tags_asc, &a, &b), Ordering::Less);
+        assert_eq!(compare_asc_desc(&tags_desc, &a, &b), Ordering::Less);
+    }
+}
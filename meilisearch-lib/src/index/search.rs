@@ -1,21 +1,31 @@
-use std::cmp::min;
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::cmp::{min, Ordering};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 use std::time::Instant;
 
 use either::Either;
+use milli::heed::types::ByteSlice;
+use milli::heed::{self, RoTxn};
+use milli::heed_codec::facet::{FacetLevelValueF64Codec, FacetStringLevelZeroCodec};
 use milli::tokenizer::{Analyzer, AnalyzerConfig};
 use milli::{
-    AscDesc, FieldId, FieldsIdsMap, Filter, FormatOptions, MatchBounds, MatcherBuilder, SortError,
+    AscDesc, DocumentId, FieldId, FieldsIdsMap, Filter, FormatOptions, MatchBounds, MatcherBuilder,
+    SortError,
 };
+use once_cell::sync::Lazy;
 use regex::Regex;
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use siphasher::sip::SipHasher24;
 
 use crate::index::error::FacetError;
 
 use super::error::{IndexError, Result};
 use super::index::Index;
+use super::ranking::{ranking_scores, RankingOverrides};
+use super::vectors;
 
 pub type Document = serde_json::Map<String, Value>;
 type MatchesInfo = BTreeMap<String, Vec<MatchBounds>>;
@@ -49,6 +59,17 @@ pub fn default_highlight_post_tag() -> String {
 /// will be able to return in one search call.
 pub const HARD_RESULT_LIMIT: usize = 1000;
 
+/// The maximum number of values returned by milli for each facet of a facet distribution.
+const MAX_VALUES_PER_FACET: usize = 100;
+
+pub const DEFAULT_SEMANTIC_RATIO: f32 = 0.5;
+const fn default_semantic_ratio() -> f32 {
+    DEFAULT_SEMANTIC_RATIO
+}
+
+/// The rank at which the keyword score of a hit is halved when blended with its semantic score.
+const KEYWORD_RANK_DECAY: f32 = 60.;
+
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SearchQuery {
@@ -73,6 +94,48 @@ pub struct SearchQuery {
     pub highlight_post_tag: String,
     #[serde(default = "default_crop_marker")]
     pub crop_marker: String,
+    /// Fetches the page following a previous one, even beyond the `HARD_RESULT_LIMIT`. `offset` is
+    /// then ignored. The hits before the cursor are still ranked again, so a deep page costs as
+    /// much as a large offset. A cursor is only valid with the query that returned it, and until
+    /// the instance restarts.
+    pub cursor: Option<String>,
+    /// Counts the hits exactly even when it requires to rank all the matching documents, which is
+    /// O(number of documents).
+    #[serde(default = "Default::default")]
+    pub exhaustive_counts: bool,
+    /// Searches only these searchable attributes.
+    pub attributes_to_search_on: Option<Vec<String>>,
+    /// Ranks the hits with a reordering of the ranking rules of the index.
+    pub ranking_rules: Option<Vec<String>>,
+    /// Returns the relevancy of each hit, between 0 and 1, in `_rankingScore`.
+    #[serde(default = "Default::default")]
+    pub show_ranking_score: bool,
+    /// Returns the ranks of each hit on the ranking rules in `_rankingScoreDetails`.
+    #[serde(default = "Default::default")]
+    pub show_ranking_score_details: bool,
+    #[serde(default = "Default::default")]
+    pub matching_strategy: MatchingStrategy,
+    /// Ranks the documents by the similarity of their `_vectors` with this one too.
+    pub vector: Option<Vec<f32>>,
+    /// The weight of the semantic ranking over the keyword ranking, between 0 and 1.
+    #[serde(default = "default_semantic_ratio")]
+    pub semantic_ratio: f32,
+}
+
+/// How the words of a query are matched when not enough documents contain all of them.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchingStrategy {
+    /// The words are removed from the last one until enough documents are found.
+    Last,
+    /// Only the documents containing all the words are returned.
+    All,
+}
+
+impl Default for MatchingStrategy {
+    fn default() -> Self {
+        Self::Last
+    }
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
@@ -83,6 +146,13 @@ pub struct SearchHit {
     pub formatted: Document,
     #[serde(rename = "_matchesInfo", skip_serializing_if = "Option::is_none")]
     pub matches_info: Option<MatchesInfo>,
+    #[serde(rename = "_rankingScore", skip_serializing_if = "Option::is_none")]
+    pub ranking_score: Option<f64>,
+    #[serde(
+        rename = "_rankingScoreDetails",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ranking_score_details: Option<Document>,
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
@@ -99,54 +169,366 @@ pub struct SearchResult {
     pub facets_distribution: Option<BTreeMap<String, BTreeMap<String, u64>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exhaustive_facets_count: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_stats: Option<BTreeMap<String, FacetStats>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct FacetStats {
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FacetSearchQuery {
+    pub facet_name: String,
+    pub facet_query: Option<String>,
+    pub q: Option<String>,
+    pub filter: Option<Value>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FacetHit {
+    pub value: String,
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetSearchResult {
+    pub facet_hits: Vec<FacetHit>,
+    pub facet_query: Option<String>,
+    pub processing_time_ms: u128,
+}
+
+/// The key authenticating the search cursors. It is drawn when the process starts, the cursors
+/// returned before a restart are then rejected.
+static CURSOR_KEY: Lazy<(u64, u64)> = Lazy::new(rand::random);
+
+/// The ranking position following the last hit returned by a search. It only lifts the
+/// `HARD_RESULT_LIMIT` on the offset: milli doesn't expose the state of its ranking rules, so the
+/// search is ranked again from the first hit for each page.
+///
+/// The cursor is encoded along with a tag computed over its content and the query that ranked it,
+/// it can't be forged nor reused with another query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SearchCursor {
+    /// The ranking position of the first hit of the next page.
+    position: u32,
+    /// The internal id of the hit preceding `position`.
+    last_id: DocumentId,
+}
+
+impl SearchCursor {
+    fn encode(&self, query: &SearchQuery) -> String {
+        format!(
+            "{:08x}{:08x}{:016x}",
+            self.position,
+            self.last_id,
+            self.tag(query)
+        )
+    }
+
+    fn decode(cursor: &str, query: &SearchQuery) -> Option<Self> {
+        if cursor.len() != 32 || !cursor.is_ascii() {
+            return None;
+        }
+
+        let position = u32::from_str_radix(&cursor[..8], 16).ok()?;
+        let last_id = u32::from_str_radix(&cursor[8..16], 16).ok()?;
+        let tag = u64::from_str_radix(&cursor[16..], 16).ok()?;
+        let cursor = Self { position, last_id };
+        // a cursor always points after at least one hit.
+        (position > 0 && cursor.tag(query) == tag).then(|| cursor)
+    }
+
+    /// Authenticates the cursor and the parameters of the query that change the ranking.
+    fn tag(&self, query: &SearchQuery) -> u64 {
+        let (k0, k1) = *CURSOR_KEY;
+        let mut hasher = SipHasher24::new_with_keys(k0, k1);
+        self.position.hash(&mut hasher);
+        self.last_id.hash(&mut hasher);
+        query.q.hash(&mut hasher);
+        query
+            .filter
+            .as_ref()
+            .map(Value::to_string)
+            .hash(&mut hasher);
+        query.sort.hash(&mut hasher);
+        (query.matching_strategy == MatchingStrategy::Last).hash(&mut hasher);
+        hasher.finish()
+    }
 }
 
 impl Index {
     pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
+        if query.vector.is_some() && !(0.0..=1.0).contains(&query.semantic_ratio) {
+            return Err(IndexError::InvalidSemanticRatio(query.semantic_ratio));
+        }
+
         let before_search = Instant::now();
         let rtxn = self.read_txn()?;
 
+        let filter = query.filter.as_ref().map(expand_geo_bounding_boxes);
         let mut search = self.search(&rtxn);
 
         if let Some(ref query) = query.q {
             search.query(query);
         }
 
-        // Make sure that a user can't get more documents than the hard limit,
-        // we align that on the offset too.
-        let offset = min(query.offset.unwrap_or(0), HARD_RESULT_LIMIT);
-        let limit = min(query.limit, HARD_RESULT_LIMIT.saturating_sub(offset));
+        search.optional_words(query.matching_strategy == MatchingStrategy::Last);
 
-        search.offset(offset);
-        search.limit(limit);
+        let cursor = match query.cursor {
+            Some(ref cursor) => Some(
+                SearchCursor::decode(cursor, &query)
+                    .ok_or_else(|| IndexError::InvalidSearchCursor(cursor.clone()))?,
+            ),
+            None => None,
+        };
+
+        let overrides = RankingOverrides::new(
+            self,
+            &rtxn,
+            query.attributes_to_search_on.as_deref(),
+            query.ranking_rules.as_deref(),
+        )?;
+
+        // The overrides only rank again the hits within the hard limit, that a cursor pages beyond.
+        if overrides.is_some() && cursor.is_some() {
+            return Err(IndexError::SearchCursorWithOverrides);
+        }
+
+        // The hybrid ranking is blended from the first hit, paging it with a cursor would rank all
+        // the hits before the cursor.
+        if query.cursor.is_some() && query.vector.is_some() {
+            return Err(IndexError::SearchCursorWithVector);
+        }
+
+        // The hits are ordered by their blended score, which ignores the sort criteria.
+        if query.sort.is_some() && query.vector.is_some() {
+            return Err(IndexError::SortWithVector);
+        }
 
-        if let Some(ref filter) = query.filter {
+        // The overrides rank again the hits found by milli, not the blended ones.
+        if overrides.is_some() && query.vector.is_some() {
+            return Err(IndexError::SearchOverridesWithVector);
+        }
+
+        let (offset, limit) = match cursor {
+            // The hit preceding the cursor is fetched again to make sure that the ranking did not
+            // change since the previous page was returned.
+            Some(cursor) => (
+                cursor.position as usize - 1,
+                min(query.limit, HARD_RESULT_LIMIT) + 1,
+            ),
+            // Make sure that a user can't get more documents than the hard limit,
+            // we align that on the offset too.
+            None => {
+                let offset = min(query.offset.unwrap_or(0), HARD_RESULT_LIMIT);
+                (
+                    offset,
+                    min(query.limit, HARD_RESULT_LIMIT.saturating_sub(offset)),
+                )
+            }
+        };
+
+        match overrides {
+            // milli ranks the hits with the settings of the index, they are ranked again up to the
+            // hard limit.
+            Some(_) => search.offset(0).limit(HARD_RESULT_LIMIT),
+            None => search.offset(offset).limit(limit),
+        };
+
+        if let Some(ref filter) = filter {
             if let Some(facets) = parse_filter(filter)? {
                 search.filter(facets);
             }
         }
 
-        if let Some(ref sort) = query.sort {
-            let sort = match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
+        let sort: Vec<AscDesc> = match query.sort {
+            Some(ref sort) => match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
                 Ok(sorts) => sorts,
                 Err(asc_desc_error) => {
                     return Err(IndexError::Milli(SortError::from(asc_desc_error).into()))
                 }
-            };
+            },
+            None => Vec::new(),
+        };
 
-            search.sort_criteria(sort);
+        if query.sort.is_some() {
+            search.sort_criteria(sort.clone());
         }
 
-        let milli::SearchResult {
-            documents_ids,
-            matching_words,
-            candidates,
-            ..
-        } = search.execute()?;
+        // The blended score of the hits of a hybrid search, and their semantic similarity.
+        let mut hybrid_scores: HashMap<DocumentId, (f32, Option<f32>)> = HashMap::new();
+        let (mut documents_ids, matching_words, candidates) = match query.vector {
+            Some(ref vector) => {
+                // The keyword ranking of a placeholder search only follows the documents ids, it is
+                // left out of the blend and only gives the documents matching the filter.
+                let has_words = query.q.as_deref().map_or(false, |q| !q.trim().is_empty());
+
+                // The hits of both rankings are blended from the first one, up to the end of the page.
+                search
+                    .offset(0)
+                    .limit(if has_words { offset + limit } else { 0 });
+                let keyword = search.execute()?;
+
+                // The semantic ranking considers all the documents matching the filter, whether they
+                // contain the query words or not.
+                let filtered = if has_words {
+                    let mut semantic_search = self.search(&rtxn);
+                    if let Some(ref filter) = filter {
+                        if let Some(facets) = parse_filter(filter)? {
+                            semantic_search.filter(facets);
+                        }
+                    }
+                    semantic_search.limit(0);
+                    semantic_search.execute()?.candidates
+                } else {
+                    keyword.candidates.clone()
+                };
+                let semantic = self.semantic_ranking(&rtxn, filtered.iter(), vector)?;
+
+                let blended =
+                    blend_rankings(&keyword.documents_ids, &semantic, query.semantic_ratio);
+                let similarities: HashMap<_, _> = semantic.iter().copied().collect();
+                let documents_ids = blended
+                    .into_iter()
+                    .skip(offset)
+                    .take(limit)
+                    .map(|(id, score)| {
+                        hybrid_scores.insert(id, (score, similarities.get(&id).copied()));
+                        id
+                    })
+                    .collect();
+                let semantic_ids = semantic.iter().map(|&(id, _)| id);
+                let candidates = if has_words {
+                    let mut candidates = keyword.candidates;
+                    candidates.extend(semantic_ids);
+                    candidates
+                } else {
+                    semantic_ids.collect()
+                };
+
+                (documents_ids, keyword.matching_words, candidates)
+            }
+            None => {
+                let milli::SearchResult {
+                    documents_ids,
+                    matching_words,
+                    candidates,
+                    ..
+                } = search.execute()?;
+                (documents_ids, matching_words, candidates)
+            }
+        };
+
+        // The candidates are exact for a placeholder search, or when the ranking rules went through
+        // all their buckets, which is the case when less hits than requested were found.
+        let placeholder = query.q.as_deref().map_or(true, |q| q.trim().is_empty());
+        let mut exhaustive_nb_hits = placeholder || documents_ids.len() < limit;
+
+        let (candidates, overridden) = match overrides {
+            Some(ref overrides) => {
+                let window_is_complete = documents_ids.len() < HARD_RESULT_LIMIT;
+                let ranked =
+                    overrides.rerank(self, &rtxn, query.q.as_deref(), &sort, &documents_ids)?;
+                documents_ids = ranked.iter().skip(offset).take(limit).copied().collect();
+
+                let candidates = if window_is_complete {
+                    exhaustive_nb_hits = true;
+                    ranked.into_iter().collect()
+                } else {
+                    // the hits beyond the hard limit are not matched against the searched
+                    // attributes, the count is then an upper bound.
+                    exhaustive_nb_hits = !overrides.restricted() && exhaustive_nb_hits;
+                    candidates
+                };
+                (candidates, true)
+            }
+            None => (candidates, false),
+        };
+
+        // Requesting all the documents forces the ranking rules to go through all their buckets,
+        // this is O(number of documents) in time and memory, which is why it must be opted in.
+        let candidates = if query.exhaustive_counts && !exhaustive_nb_hits && query.vector.is_none()
+        {
+            let number_of_documents = self.number_of_documents(&rtxn)? as usize;
+            let all_ids = search
+                .offset(0)
+                .limit(number_of_documents)
+                .execute()?
+                .documents_ids;
+            exhaustive_nb_hits = true;
+
+            let all_ids = match overrides {
+                Some(ref overrides) if overrides.restricted() => {
+                    overrides.matching(self, &rtxn, query.q.as_deref(), &all_ids)?
+                }
+                _ => all_ids,
+            };
+
+            RoaringBitmap::from_iter(all_ids)
+        } else {
+            candidates
+        };
+
+        let offset = match cursor {
+            Some(cursor) => {
+                if cursor.position as u64 > candidates.len()
+                    || documents_ids.first() != Some(&cursor.last_id)
+                {
+                    return Err(IndexError::InvalidSearchCursor(cursor.encode(&query)));
+                }
+                documents_ids.remove(0);
+                cursor.position as usize
+            }
+            None => offset,
+        };
+
+        let next_position = offset + documents_ids.len();
+        let next_cursor = match documents_ids.last() {
+            Some(&last_id) if !overridden && (next_position as u64) < candidates.len() => Some(
+                SearchCursor {
+                    position: next_position as u32,
+                    last_id,
+                }
+                .encode(&query),
+            ),
+            _ => None,
+        };
+
+        let mut ranking_scores = if query.show_ranking_score || query.show_ranking_score_details {
+            let mut scores = ranking_scores(
+                self,
+                &rtxn,
+                overrides.as_ref(),
+                query.q.as_deref(),
+                &sort,
+                &documents_ids,
+            )?;
+            // The hits of a hybrid search are ordered by their blended score.
+            for (id, score) in scores.iter_mut() {
+                if let Some(&(blended, similarity)) = hybrid_scores.get(id) {
+                    score.score = blended as f64;
+                    let order = score.details.len();
+                    score.details.insert(
+                        "semantic".to_string(),
+                        json!({ "order": order, "similarity": similarity }),
+                    );
+                }
+            }
+            scores
+        } else {
+            HashMap::new()
+        };
 
         let fields_ids_map = self.fields_ids_map(&rtxn).unwrap();
 
-        let displayed_ids = self
+        let mut displayed_ids = self
             .displayed_fields_ids(&rtxn)?
             .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
             .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
@@ -211,7 +593,7 @@ impl Index {
 
         let documents_iter = self.documents(&rtxn, documents_ids)?;
 
-        for (_id, obkv) in documents_iter {
+        for (id, obkv) in documents_iter {
             // First generate a document with all the displayed fields
             let displayed_document = make_document(&displayed_ids, &fields_ids_map, obkv)?;
 
@@ -232,20 +614,44 @@ impl Index {
                 &displayed_ids,
             )?;
 
-            if let Some(sort) = query.sort.as_ref() {
-                insert_geo_distance(sort, &mut document);
-            }
+            insert_geo_distance(
+                query.sort.as_deref().unwrap_or_default(),
+                query.filter.as_ref(),
+                &mut document,
+            );
 
+            let ranking_score = ranking_scores.remove(&id);
             let hit = SearchHit {
                 document,
                 formatted,
                 matches_info,
+                ranking_score: ranking_score
+                    .as_ref()
+                    .filter(|_| query.show_ranking_score)
+                    .map(|ranking_score| ranking_score.score),
+                ranking_score_details: ranking_score
+                    .filter(|_| query.show_ranking_score_details)
+                    .map(|ranking_score| ranking_score.details),
             };
             documents.push(hit);
         }
 
         let nb_hits = candidates.len();
 
+        let facet_stats = match query.facets_distribution {
+            Some(ref fields) => {
+                let facets: Vec<String> = if fields.iter().all(|f| f != "*") {
+                    fields.clone()
+                } else {
+                    self.filterable_fields(&rtxn)?.into_iter().collect()
+                };
+                let stats = self.facet_stats(&rtxn, &facets, &candidates, &fields_ids_map)?;
+
+                Some(stats)
+            }
+            None => None,
+        };
+
         let facets_distribution = match query.facets_distribution {
             Some(ref fields) => {
                 let mut facets_distribution = self.facets_distribution(&rtxn);
@@ -259,29 +665,310 @@ impl Index {
             None => None,
         };
 
-        let exhaustive_facets_count = facets_distribution.as_ref().map(|_| false); // not implemented yet
+        // The counts are exact when they were computed on exact candidates, but a facet may have
+        // more values than the ones returned.
+        let exhaustive_facets_count = facets_distribution.as_ref().map(|distribution| {
+            exhaustive_nb_hits
+                && distribution
+                    .values()
+                    .all(|values| values.len() < MAX_VALUES_PER_FACET)
+        });
 
         let result = SearchResult {
-            exhaustive_nb_hits: false, // not implemented yet
+            exhaustive_nb_hits,
             hits: documents,
             nb_hits,
             query: query.q.clone().unwrap_or_default(),
             limit: query.limit,
-            offset: query.offset.unwrap_or_default(),
+            offset,
             processing_time_ms: before_search.elapsed().as_millis(),
             facets_distribution,
             exhaustive_facets_count,
+            facet_stats,
+            next_cursor,
         };
         Ok(result)
     }
+
+    /// Computes the min and max of the numeric values of the facets over the candidates. The level
+    /// zero of the numeric facets database holds the values of a field in increasing order, so it
+    /// is only read from both ends until a value of a candidate is found. The facets without any
+    /// numeric value are left out.
+    fn facet_stats(
+        &self,
+        rtxn: &RoTxn,
+        facets: &[String],
+        candidates: &RoaringBitmap,
+        fields_ids_map: &FieldsIdsMap,
+    ) -> Result<BTreeMap<String, FacetStats>> {
+        let db = self.facet_id_f64_docids.remap_key_type::<ByteSlice>();
+
+        let mut stats = BTreeMap::new();
+        for facet in facets {
+            let field_id = match fields_ids_map.id(facet) {
+                Some(field_id) => field_id,
+                None => continue,
+            };
+            // The keys are prefixed by the field id and the level.
+            let mut prefix = field_id.to_be_bytes().to_vec();
+            prefix.push(0);
+
+            let ascending = db
+                .prefix_iter(rtxn, &prefix)?
+                .remap_key_type::<FacetLevelValueF64Codec>();
+            let min = match first_candidate_value(ascending, candidates)? {
+                Some(min) => min,
+                None => continue,
+            };
+            let descending = db
+                .rev_prefix_iter(rtxn, &prefix)?
+                .remap_key_type::<FacetLevelValueF64Codec>();
+            let max = first_candidate_value(descending, candidates)?.unwrap_or(min);
+
+            stats.insert(facet.clone(), FacetStats { min, max });
+        }
+
+        Ok(stats)
+    }
+
+    /// Returns the candidates having a vector of the same dimensions as the query vector, ranked
+    /// by their cosine similarity with it. The best of its vectors is used for a document.
+    fn semantic_ranking(
+        &self,
+        rtxn: &RoTxn,
+        candidates: impl Iterator<Item = DocumentId>,
+        vector: &[f32],
+    ) -> Result<Vec<(DocumentId, f32)>> {
+        let mut ranking = Vec::new();
+        let mut has_vectors = false;
+        for id in candidates {
+            let vectors = match vectors::get(self, rtxn, id)? {
+                Some(vectors) => vectors,
+                None => continue,
+            };
+            has_vectors = true;
+            let similarity = document_vectors(&vectors)
+                .filter(|document_vector| document_vector.len() == vector.len())
+                .map(|document_vector| cosine_similarity(vector, &document_vector))
+                .reduce(f32::max);
+            if let Some(similarity) = similarity {
+                ranking.push((id, similarity));
+            }
+        }
+
+        // A vector that can't be compared to any of the candidates having vectors is most likely
+        // a mistake.
+        if ranking.is_empty() && has_vectors {
+            return Err(IndexError::InvalidVectorDimensions(vector.len()));
+        }
+
+        ranking.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        Ok(ranking)
+    }
+
+    pub fn perform_facet_search(&self, query: FacetSearchQuery) -> Result<FacetSearchResult> {
+        let before_search = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let filterable_fields = self.filterable_fields(&rtxn)?;
+        let is_filterable = filterable_fields.iter().any(|field| {
+            query.facet_name == *field || query.facet_name.starts_with(&format!("{}.", field))
+        });
+        if !is_filterable {
+            return Err(FacetError::AttributeNotFilterable(query.facet_name).into());
+        }
+
+        let filter = query.filter.as_ref().map(expand_geo_bounding_boxes);
+        let mut search = self.search(&rtxn);
+
+        if let Some(ref query) = query.q {
+            search.query(query);
+        }
+
+        if let Some(ref filter) = filter {
+            if let Some(facets) = parse_filter(filter)? {
+                search.filter(facets);
+            }
+        }
+
+        // Only the candidates are needed to count the documents of each facet value.
+        search.limit(0);
+        let candidates = search.execute()?.candidates;
+
+        let facet_query: Vec<char> = query
+            .facet_query
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .chars()
+            .collect();
+        let allowed_typos = if !self.authorize_typos(&rtxn)? {
+            0
+        } else if facet_query.len() >= self.min_word_len_two_typos(&rtxn)? as usize {
+            2
+        } else if facet_query.len() >= self.min_word_len_one_typo(&rtxn)? as usize {
+            1
+        } else {
+            0
+        };
+
+        let mut facet_hits = Vec::new();
+        if let Some(field_id) = self.fields_ids_map(&rtxn)?.id(&query.facet_name) {
+            // The level zero of the string facets database holds all the values of the field, with
+            // the documents that contain them. Its keys are prefixed by the field id and the level.
+            let mut prefix = field_id.to_be_bytes().to_vec();
+            prefix.push(0);
+
+            let iter = self
+                .facet_id_string_docids
+                .remap_key_type::<ByteSlice>()
+                .prefix_iter(&rtxn, &prefix)?
+                .remap_key_type::<FacetStringLevelZeroCodec>();
+
+            for result in iter {
+                let ((_, _normalized), (original, mut docids)) = result?;
+                if !facet_value_matches(original, &facet_query, allowed_typos) {
+                    continue;
+                }
+
+                docids &= &candidates;
+                if !docids.is_empty() {
+                    facet_hits.push(FacetHit {
+                        value: original.to_string(),
+                        count: docids.len(),
+                    });
+                }
+            }
+        }
+        facet_hits.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+
+        Ok(FacetSearchResult {
+            facet_hits,
+            facet_query: query.facet_query,
+            processing_time_ms: before_search.elapsed().as_millis(),
+        })
+    }
+}
+
+/// Returns the vectors of a `_vectors` field, that holds either one vector or an array of vectors.
+fn document_vectors(vectors: &Value) -> Box<dyn Iterator<Item = Vec<f32>> + '_> {
+    let as_vector = |value: &Value| -> Option<Vec<f32>> {
+        value
+            .as_array()?
+            .iter()
+            .map(|n| n.as_f64().map(|n| n as f32))
+            .collect()
+    };
+
+    match vectors {
+        Value::Array(values) if values.iter().all(Value::is_array) => {
+            Box::new(values.iter().filter_map(as_vector))
+        }
+        vector => Box::new(as_vector(vector).into_iter()),
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0. {
+        0.
+    } else {
+        dot / norms
+    }
+}
+
+/// Blends the keyword ranking with the semantic ranking. The keyword score decreases with the rank
+/// of the hit, the semantic score is the cosine similarity brought between 0 and 1. The hits are
+/// returned with their blended score.
+fn blend_rankings(
+    keyword: &[DocumentId],
+    semantic: &[(DocumentId, f32)],
+    semantic_ratio: f32,
+) -> Vec<(DocumentId, f32)> {
+    let mut scores: HashMap<DocumentId, f32> = HashMap::new();
+    for (rank, &id) in keyword.iter().enumerate() {
+        let score = KEYWORD_RANK_DECAY / (KEYWORD_RANK_DECAY + rank as f32);
+        *scores.entry(id).or_default() += (1. - semantic_ratio) * score;
+    }
+    for &(id, similarity) in semantic {
+        *scores.entry(id).or_default() += semantic_ratio * (similarity + 1.) / 2.;
+    }
+
+    let mut ranking: Vec<_> = scores.into_iter().collect();
+    // the ties are broken by the document id to keep the pages stable.
+    ranking.sort_by(|(a_id, a), (b_id, b)| {
+        b.partial_cmp(a)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a_id.cmp(b_id))
+    });
+    ranking
+}
+
+/// Returns the first facet value of `iter` that is contained by one of the candidates.
+fn first_candidate_value(
+    iter: impl Iterator<Item = heed::Result<((FieldId, u8, f64, f64), RoaringBitmap)>>,
+    candidates: &RoaringBitmap,
+) -> Result<Option<f64>> {
+    for result in iter {
+        let ((_, _, value, _), docids) = result?;
+        if !docids.is_disjoint(candidates) {
+            return Ok(Some(value));
+        }
+    }
+
+    Ok(None)
 }
 
-fn insert_geo_distance(sorts: &[String], document: &mut Document) {
+/// Returns whether a word of the facet value starts with the query, with at most `allowed_typos`
+/// typos.
+fn facet_value_matches(value: &str, query: &[char], allowed_typos: usize) -> bool {
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+    (0..value.len())
+        .filter(|&i| i == 0 || value[i - 1].is_whitespace())
+        .any(|i| prefix_distance(query, &value[i..]) <= allowed_typos)
+}
+
+/// Computes the smallest edit distance between the query and any prefix of the text.
+fn prefix_distance(query: &[char], text: &[char]) -> usize {
+    // `previous[j]` is the distance between the query processed so far and `text[..j]`.
+    let mut previous: Vec<usize> = (0..=text.len()).collect();
+    for (i, query_char) in query.iter().enumerate() {
+        let mut current = vec![i + 1; text.len() + 1];
+        for (j, text_char) in text.iter().enumerate() {
+            let substitution = previous[j] + (query_char != text_char) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous.into_iter().min().unwrap_or_default()
+}
+
+/// Inserts the distance to the point of a `_geoPoint` sort, or else to the center of a `_geoRadius`
+/// filter.
+fn insert_geo_distance(sorts: &[String], filter: Option<&Value>, document: &mut Document) {
     lazy_static::lazy_static! {
         static ref GEO_REGEX: Regex =
             Regex::new(r"_geoPoint\(\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*\)").unwrap();
+        static ref GEO_RADIUS_REGEX: Regex =
+            Regex::new(r"_geoRadius\(\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*,").unwrap();
     };
-    if let Some(capture_group) = sorts.iter().find_map(|sort| GEO_REGEX.captures(sort)) {
+    let mut filter_expressions = Vec::new();
+    if let Some(filter) = filter {
+        collect_filter_expressions(filter, &mut filter_expressions);
+    }
+
+    let capture_group = sorts
+        .iter()
+        .find_map(|sort| GEO_REGEX.captures(sort))
+        .or_else(|| {
+            filter_expressions
+                .iter()
+                .find_map(|&expr| GEO_RADIUS_REGEX.captures(expr))
+        });
+    if let Some(capture_group) = capture_group {
         // TODO: TAMO: milli encountered an internal error, what do we want to do?
         let base = [
             capture_group[1].parse().unwrap(),
@@ -295,6 +982,133 @@ fn insert_geo_distance(sorts: &[String], document: &mut Document) {
     }
 }
 
+fn collect_filter_expressions<'a>(filter: &'a Value, expressions: &mut Vec<&'a str>) {
+    match filter {
+        Value::String(expr) => expressions.push(expr),
+        Value::Array(filters) => filters
+            .iter()
+            .for_each(|filter| collect_filter_expressions(filter, expressions)),
+        _ => (),
+    }
+}
+
+/// Rewrites the `_geoBoundingBox([top, right], [bottom, left])` rules of a filter into ranges on the
+/// coordinates of the `_geo` field, that milli knows how to evaluate.
+///
+/// The ranges only match the coordinates indexed as numbers: the documents whose `_geo.lat` or
+/// `_geo.lng` is a string are not matched by a bounding box, even though `_geoRadius` matches them.
+/// There is no polygon rule, milli can only evaluate the geo points within a radius.
+pub(super) fn expand_geo_bounding_boxes(filter: &Value) -> Value {
+    lazy_static::lazy_static! {
+        static ref GEO_BOUNDING_BOX_REGEX: Regex = Regex::new(concat!(
+            r"_geoBoundingBox\(\s*\[\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*\]\s*,",
+            r"\s*\[\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*\]\s*\)"
+        ))
+        .unwrap();
+    };
+
+    match filter {
+        Value::String(expr) => {
+            let mut rewritten = String::with_capacity(expr.len());
+            for (quoted, part) in split_quoted_values(expr) {
+                if quoted {
+                    rewritten.push_str(part);
+                    continue;
+                }
+
+                let mut last = 0;
+                for captures in GEO_BOUNDING_BOX_REGEX.captures_iter(part) {
+                    let rule = captures.get(0).unwrap();
+                    rewritten.push_str(&part[last..rule.start()]);
+                    last = rule.end();
+
+                    let coordinates: Option<Vec<f64>> =
+                        (1..=4).map(|i| captures[i].parse().ok()).collect();
+                    match coordinates.as_deref() {
+                        Some(&[top, right, bottom, left]) => {
+                            let bounding_box = GeoBoundingBox {
+                                top,
+                                right,
+                                bottom,
+                                left,
+                            };
+                            rewritten.push_str(&bounding_box.to_ranges())
+                        }
+                        // The rule is left as is for milli to report the invalid filter.
+                        _ => rewritten.push_str(rule.as_str()),
+                    }
+                }
+                rewritten.push_str(&part[last..]);
+            }
+            Value::String(rewritten)
+        }
+        Value::Array(filters) => {
+            Value::Array(filters.iter().map(expand_geo_bounding_boxes).collect())
+        }
+        filter => filter.clone(),
+    }
+}
+
+/// A `_geoBoundingBox([top, right], [bottom, left])` rule of a filter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct GeoBoundingBox {
+    top: f64,
+    right: f64,
+    bottom: f64,
+    left: f64,
+}
+
+impl GeoBoundingBox {
+    /// A box whose left side is east of its right side crosses the antimeridian.
+    fn crosses_antimeridian(&self) -> bool {
+        self.left > self.right
+    }
+
+    /// The ranges on the numeric coordinates of the `_geo` field matching the box.
+    fn to_ranges(&self) -> String {
+        let lng_operator = if self.crosses_antimeridian() {
+            "OR"
+        } else {
+            "AND"
+        };
+        format!(
+            "(_geo.lat >= {} AND _geo.lat <= {} AND (_geo.lng >= {} {} _geo.lng <= {}))",
+            self.bottom, self.top, self.left, lng_operator, self.right
+        )
+    }
+}
+
+/// Splits a filter expression into the values quoted with `'` or `"` and the parts between them.
+/// Each part is returned with whether it is quoted.
+fn split_quoted_values(expr: &str) -> Vec<(bool, &str)> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in expr.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => {
+                parts.push((true, &expr[start..=i]));
+                start = i + 1;
+                quote = None;
+            }
+            Some(_) => (),
+            None if c == '"' || c == '\'' => {
+                parts.push((false, &expr[start..i]));
+                start = i;
+                quote = Some(c);
+            }
+            None => (),
+        }
+    }
+    parts.push((quote.is_some(), &expr[start..]));
+
+    parts
+}
+
 fn compute_formatted_options(
     attr_to_highlight: &HashSet<String>,
     attr_to_crop: &[String],
@@ -585,7 +1399,7 @@ fn format_value<'a, A: AsRef<[u8]>>(
     }
 }
 
-fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
+pub(super) fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
     match facets {
         Value::String(expr) => {
             let condition = Filter::from_str(expr)?;
@@ -628,6 +1442,74 @@ fn parse_filter_array(arr: &[Value]) -> Result<Option<Filter>> {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_document_vectors() {
+        let vectors: Vec<_> = document_vectors(&json!([1, 0.5])).collect();
+        assert_eq!(vectors, vec![vec![1., 0.5]]);
+
+        let vectors: Vec<_> = document_vectors(&json!([[1, 0], [0, "a"], [0, 1]])).collect();
+        assert_eq!(vectors, vec![vec![1., 0.], vec![0., 1.]]);
+
+        assert_eq!(document_vectors(&json!("hello")).count(), 0);
+    }
+
+    #[test]
+    fn test_blend_rankings() {
+        let keyword = [1, 2, 3];
+        let semantic = [(3, 1.), (4, 0.5), (1, -1.)];
+
+        let ids = |ratio| -> Vec<_> {
+            blend_rankings(&keyword, &semantic, ratio)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        };
+        assert_eq!(ids(0.), vec![1, 2, 3, 4]);
+        assert_eq!(ids(1.), vec![3, 4, 1, 2]);
+        assert_eq!(ids(0.5), vec![3, 1, 2, 4]);
+
+        // the hits are returned with their blended score.
+        assert_eq!(blend_rankings(&keyword, &semantic, 1.)[0], (3, 1.));
+    }
+
+    #[test]
+    fn test_facet_value_matches() {
+        let query: Vec<char> = "nik".chars().collect();
+        assert!(facet_value_matches("Nike", &query, 0));
+        assert!(facet_value_matches("Air Nike", &query, 0));
+        assert!(!facet_value_matches("Snikers", &query, 0));
+        assert!(!facet_value_matches("Nuke", &query, 0));
+        assert!(facet_value_matches("Nuke", &query, 1));
+        assert!(facet_value_matches("anything", &[], 0));
+
+        let query: Vec<char> = "adiddas".chars().collect();
+        assert!(facet_value_matches("Adidas", &query, 1));
+        assert!(!facet_value_matches("Asics", &query, 1));
+    }
+
+    #[test]
+    fn test_search_cursor() {
+        let query: SearchQuery = serde_json::from_value(json!({ "q": "hello" })).unwrap();
+        let cursor = SearchCursor {
+            position: 1042,
+            last_id: 7,
+        };
+        let encoded = cursor.encode(&query);
+        assert_eq!(SearchCursor::decode(&encoded, &query), Some(cursor));
+
+        // the cursor is bound to the query that ranked it.
+        let other_query: SearchQuery = serde_json::from_value(json!({ "q": "world" })).unwrap();
+        assert_eq!(SearchCursor::decode(&encoded, &other_query), None);
+
+        // the position can't be changed without the tag.
+        let forged = format!("{:08x}{}", 2042, &encoded[8..]);
+        assert_eq!(SearchCursor::decode(&forged, &query), None);
+
+        assert_eq!(SearchCursor::decode("", &query), None);
+        assert_eq!(SearchCursor::decode("0000000000000007", &query), None);
+        assert_eq!(SearchCursor::decode(&encoded[1..], &query), None);
+    }
+
     #[test]
     fn test_insert_geo_distance() {
         let value: Document = serde_json::from_str(
@@ -644,18 +1526,18 @@ mod test {
 
         let sorters = &["_geoPoint(50.629973371633746,3.0569447399419567):desc".to_string()];
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(sorters, None, &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         let sorters = &["_geoPoint(50.629973371633746, 3.0569447399419567):asc".to_string()];
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(sorters, None, &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         let sorters =
             &["_geoPoint(   50.629973371633746   ,  3.0569447399419567   ):desc".to_string()];
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(sorters, None, &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         let sorters = &[
@@ -666,7 +1548,7 @@ mod test {
         ]
         .map(|s| s.to_string());
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(sorters, None, &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         // only the first geoPoint is used to compute the distance
@@ -679,13 +1561,52 @@ mod test {
         ]
         .map(|s| s.to_string());
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(sorters, None, &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         // there was no _geoPoint so nothing is inserted in the document
         let sorters = &["chien:asc".to_string()];
-        let mut document = value;
-        insert_geo_distance(sorters, &mut document);
+        let mut document = value.clone();
+        insert_geo_distance(sorters, None, &mut document);
         assert_eq!(document.get("_geoDistance"), None);
+
+        // without _geoPoint, the center of a _geoRadius filter is used
+        let filter = json!([
+            "chien = 1",
+            ["_geoRadius(50.629973371633746, 3.0569447399419567, 10)"]
+        ]);
+        let mut document = value.clone();
+        insert_geo_distance(sorters, Some(&filter), &mut document);
+        assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
+
+        let sorters = &["_geoPoint(0, 0):asc".to_string()];
+        let mut document = value;
+        insert_geo_distance(sorters, Some(&filter), &mut document);
+        assert_ne!(document.get("_geoDistance"), Some(&json!(0)));
+    }
+
+    #[test]
+    fn test_expand_geo_bounding_boxes() {
+        let filter = json!("_geoBoundingBox([45.5, 5], [43, -1.2]) AND color = red");
+        assert_eq!(
+            expand_geo_bounding_boxes(&filter),
+            json!("(_geo.lat >= 43 AND _geo.lat <= 45.5 AND (_geo.lng >= -1.2 AND _geo.lng <= 5)) AND color = red")
+        );
+
+        // crossing the antimeridian
+        let filter = json!([["_geoBoundingBox([10, -170], [-10, 170])"], "color = red"]);
+        assert_eq!(
+            expand_geo_bounding_boxes(&filter),
+            json!([["(_geo.lat >= -10 AND _geo.lat <= 10 AND (_geo.lng >= 170 OR _geo.lng <= -170))"], "color = red"])
+        );
+
+        let filter = json!("color = red");
+        assert_eq!(expand_geo_bounding_boxes(&filter), filter);
+
+        // the quoted values are left untouched.
+        let filter = json!(
+            r#"title = "_geoBoundingBox([1, 1], [0, 0])" OR name = '\'_geoBoundingBox([1, 1], [0, 0])'"#
+        );
+        assert_eq!(expand_geo_bounding_boxes(&filter), filter);
     }
 }
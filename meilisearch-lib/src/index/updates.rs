@@ -1,18 +1,28 @@
+use std::borrow::Cow;
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::{Cursor, Seek, SeekFrom};
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 
 use log::{debug, info, trace};
-use milli::documents::DocumentBatchReader;
+use milli::documents::{DocumentBatchBuilder, DocumentBatchReader};
+use milli::heed::RwTxn;
 use milli::update::{
     DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod,
     Setting,
 };
 use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Number, Value};
 use uuid::Uuid;
 
-use super::error::Result;
+use super::error::{IndexError, Result};
 use super::index::{Index, IndexMeta};
+use super::search::{expand_geo_bounding_boxes, parse_filter};
+use super::vectors::{self, VECTORS_FIELD};
 use crate::update_file_store::UpdateFileStore;
 
 fn serialize_with_wildcard<S>(
@@ -203,6 +213,74 @@ impl Settings<Unchecked> {
     }
 }
 
+impl<T> Settings<T> {
+    /// Overrides the settings of `self` with the ones that are set or reset in `other`, so that
+    /// applying the merged settings is the same as applying `self` and then `other`.
+    pub fn merge(&mut self, other: Settings<T>) {
+        merge_setting(&mut self.displayed_attributes, other.displayed_attributes);
+        merge_setting(&mut self.searchable_attributes, other.searchable_attributes);
+        merge_setting(&mut self.filterable_attributes, other.filterable_attributes);
+        merge_setting(&mut self.sortable_attributes, other.sortable_attributes);
+        merge_setting(&mut self.ranking_rules, other.ranking_rules);
+        merge_setting(&mut self.stop_words, other.stop_words);
+        merge_setting(&mut self.synonyms, other.synonyms);
+        merge_setting(&mut self.distinct_attribute, other.distinct_attribute);
+
+        merge_typo_tolerance(&mut self.typo_tolerance, other.typo_tolerance);
+    }
+}
+
+fn merge_setting<T>(current: &mut Setting<T>, other: Setting<T>) {
+    if !other.is_not_set() {
+        *current = other;
+    }
+}
+
+/// The typo tolerance is merged field by field, a reset being the same as resetting every field.
+fn merge_typo_tolerance(current: &mut Setting<TypoSettings>, other: Setting<TypoSettings>) {
+    let other = match other {
+        Setting::Set(other) => other,
+        other => return merge_setting(current, other),
+    };
+
+    let mut merged = match std::mem::replace(current, Setting::NotSet) {
+        Setting::Set(current) => current,
+        Setting::Reset => TypoSettings {
+            enabled: Setting::Reset,
+            min_word_size_for_typos: Setting::Reset,
+            disable_on_words: Setting::Reset,
+            disable_on_attributes: Setting::Reset,
+        },
+        Setting::NotSet => TypoSettings::default(),
+    };
+
+    merge_setting(&mut merged.enabled, other.enabled);
+    match other.min_word_size_for_typos {
+        Setting::Set(other) => {
+            let mut min_word_size =
+                match std::mem::replace(&mut merged.min_word_size_for_typos, Setting::NotSet) {
+                    Setting::Set(current) => current,
+                    Setting::Reset => MinWordSizeTyposSetting {
+                        one_typo: Setting::Reset,
+                        two_typos: Setting::Reset,
+                    },
+                    Setting::NotSet => MinWordSizeTyposSetting::default(),
+                };
+            merge_setting(&mut min_word_size.one_typo, other.one_typo);
+            merge_setting(&mut min_word_size.two_typos, other.two_typos);
+            merged.min_word_size_for_typos = Setting::Set(min_word_size);
+        }
+        other => merge_setting(&mut merged.min_word_size_for_typos, other),
+    }
+    merge_setting(&mut merged.disable_on_words, other.disable_on_words);
+    merge_setting(
+        &mut merged.disable_on_attributes,
+        other.disable_on_attributes,
+    );
+
+    *current = Setting::Set(merged);
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
@@ -211,6 +289,110 @@ pub struct Facets {
     pub min_level_size: Option<NonZeroUsize>,
 }
 
+/// An edit applied in place to the documents of an index. The `path` points to a field, that can
+/// be nested inside objects, e.g. `stats.views`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase", deny_unknown_fields)]
+pub enum DocumentEdit {
+    /// Sets the field to `value`.
+    Set { path: String, value: Value },
+    /// Removes the field, if it exists.
+    Remove { path: String },
+    /// Adds `by` to the field. A missing field counts as `0`.
+    Increment { path: String, by: Number },
+    /// Pushes `value` at the end of the array in the field. A missing field counts as `[]`.
+    Append { path: String, value: Value },
+}
+
+impl DocumentEdit {
+    pub fn path(&self) -> &str {
+        match self {
+            DocumentEdit::Set { path, .. }
+            | DocumentEdit::Remove { path }
+            | DocumentEdit::Increment { path, .. }
+            | DocumentEdit::Append { path, .. } => path,
+        }
+    }
+
+    /// Applies the edit to `document`, and returns whether the document was modified.
+    fn apply(&self, document: &mut Map<String, Value>) -> Result<bool> {
+        let (parents, field) = match self.path().rsplit_once('.') {
+            Some((parents, field)) => (Some(parents), field),
+            None => (None, self.path()),
+        };
+        let parents = parents.into_iter().flat_map(|parents| parents.split('.'));
+
+        if let DocumentEdit::Remove { .. } = self {
+            let mut object = document;
+            for parent in parents {
+                match object.get_mut(parent) {
+                    Some(Value::Object(inner)) => object = inner,
+                    _ => return Ok(false),
+                }
+            }
+            return Ok(object.remove(field).is_some());
+        }
+
+        let mut object = document;
+        for parent in parents {
+            match object
+                .entry(parent)
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                Value::Object(inner) => object = inner,
+                _ => return Err(self.invalid("a parent of the field is not an object")),
+            }
+        }
+
+        match self {
+            DocumentEdit::Set { value, .. } => {
+                let previous = object.insert(field.to_string(), value.clone());
+                Ok(previous.as_ref() != Some(value))
+            }
+            DocumentEdit::Increment { by, .. } => {
+                let current = match object.get(field) {
+                    None | Some(Value::Null) => Number::from(0),
+                    Some(Value::Number(current)) => current.clone(),
+                    Some(_) => return Err(self.invalid("the field is not a number")),
+                };
+                // The integers stay integers as long as they don't overflow.
+                let sum = match (current.as_i64(), by.as_i64()) {
+                    (Some(current), Some(by)) => current.checked_add(by).map(Number::from),
+                    _ => None,
+                };
+                let sum = match sum {
+                    Some(sum) => sum,
+                    None => current
+                        .as_f64()
+                        .zip(by.as_f64())
+                        .and_then(|(current, by)| Number::from_f64(current + by))
+                        .ok_or_else(|| self.invalid("the result is not a finite number"))?,
+                };
+                object.insert(field.to_string(), Value::Number(sum));
+                Ok(true)
+            }
+            DocumentEdit::Append { value, .. } => {
+                match object
+                    .entry(field)
+                    .or_insert_with(|| Value::Array(Vec::new()))
+                {
+                    Value::Array(values) => values.push(value.clone()),
+                    _ => return Err(self.invalid("the field is not an array")),
+                }
+                Ok(true)
+            }
+            DocumentEdit::Remove { .. } => unreachable!(),
+        }
+    }
+
+    fn invalid(&self, reason: &str) -> IndexError {
+        IndexError::InvalidDocumentEdit(self.path().to_string(), reason.to_string())
+    }
+}
+
+/// The number of documents read at once by a document edition.
+const EDIT_CHUNK_SIZE: usize = 1000;
+
 impl Index {
     fn update_primary_key_txn<'a, 'b>(
         &'a self,
@@ -236,6 +418,12 @@ impl Index {
     /// Deletes `ids` from the index, and returns how many documents were deleted.
     pub fn delete_documents(&self, ids: &[String]) -> Result<DocumentDeletionResult> {
         let mut txn = self.write_txn()?;
+        let deleted_ids: Vec<_> = {
+            let external_documents_ids = self.external_documents_ids(&txn)?;
+            ids.iter()
+                .filter_map(|id| external_documents_ids.get(id.as_bytes()))
+                .collect()
+        };
         let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
 
         // We ignore unexisting document ids
@@ -244,38 +432,141 @@ impl Index {
         });
 
         let deleted = builder.execute()?;
+        vectors::delete(self, &mut txn, deleted_ids)?;
 
         txn.commit()?;
 
         Ok(deleted)
     }
 
-    pub fn clear_documents(&self) -> Result<()> {
+    /// Deletes the documents matching `filter` from the index, and returns how many documents were
+    /// deleted. An empty filter matches no document.
+    pub fn delete_documents_by_filter(&self, filter: &Value) -> Result<DocumentDeletionResult> {
+        let filter = expand_geo_bounding_boxes(filter);
         let mut txn = self.write_txn()?;
-        milli::update::ClearDocuments::new(&mut txn, self).execute()?;
+
+        let candidates = match parse_filter(&filter)? {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => Default::default(),
+        };
+
+        let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
+        builder.delete_documents(&candidates);
+        let deleted = builder.execute()?;
+        vectors::delete(self, &mut txn, &candidates)?;
+
         txn.commit()?;
 
-        Ok(())
+        Ok(deleted)
     }
 
-    pub fn update_documents(
+    /// Applies `edits` to every document matching `filter`, or to all the documents when there is
+    /// no filter, and reindexes the modified documents in the same transaction.
+    pub fn edit_documents(
         &self,
-        method: IndexDocumentsMethod,
-        primary_key: Option<String>,
-        file_store: UpdateFileStore,
-        contents: impl IntoIterator<Item = Uuid>,
+        filter: Option<&Value>,
+        edits: &[DocumentEdit],
     ) -> Result<DocumentAdditionResult> {
-        trace!("performing document addition");
+        trace!("performing document edition");
+        let filter = filter.map(expand_geo_bounding_boxes);
         let mut txn = self.write_txn()?;
 
-        if let Some(primary_key) = primary_key {
-            if self.primary_key(&txn)?.is_none() {
-                self.update_primary_key_txn(&mut txn, primary_key)?;
+        // Editing the primary key would create a new document instead of modifying the current one.
+        if let Some(primary_key) = self.primary_key(&txn)? {
+            if let Some(edit) = edits.iter().find(|edit| {
+                let path = edit.path();
+                path == primary_key || path.starts_with(&format!("{}.", primary_key))
+            }) {
+                return Err(edit.invalid("the primary key can't be edited"));
             }
         }
 
+        let candidates = match filter.as_ref().map(parse_filter).transpose()?.flatten() {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => self.documents_ids(&txn)?,
+        };
+
+        let primary_key = self.primary_key(&txn)?.map(String::from);
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let external_documents_ids = self.external_documents_ids(&txn)?;
+
+        let mut content = tempfile::tempfile()?;
+        let mut batch = DocumentBatchBuilder::new(&mut content)?;
+
+        // The patches are sorted on disk by external id then by their position in the tasks, so
+        // that the patches of a document are merged together in order without keeping them all in
+        // memory. The position is appended to the external id in big endian, which keeps the
+        // patches of a document contiguous since milli doesn't accept the null byte in an id.
+        let mut sorter = grenad::SorterBuilder::new(keep_first).build();
+        let mut position = 0u64;
+        for content_uuid in contents {
+            for_each_document(&file_store, content_uuid, |patch| {
+                match external_id(primary_key.as_deref(), &patch) {
+                    Some(external_id) => {
+                        let mut key = external_id.into_bytes();
+                        key.extend_from_slice(&position.to_be_bytes());
+                        position += 1;
+                        sorter.insert(key, serde_json::to_vec(&patch)?)?;
+                    }
+                    // Without a valid external id, the document is left for milli to report the
+                    // error.
+                    None => batch.extend_from_json(Cursor::new(serde_json::to_vec(&patch)?))?,
+                }
+                Ok(())
+            })?;
+        }
+
+        let mut pending_vectors = PendingVectors::new();
+        let mut write_document = |external_id: Vec<u8>, mut document: Map<String, Value>| {
+            let external_id = String::from_utf8_lossy(&external_id).into_owned();
+            pending_vectors.push((external_id, document.remove(VECTORS_FIELD)));
+            batch.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+            Ok::<_, IndexError>(())
+        };
+
+        let mut current: Option<(Vec<u8>, Map<String, Value>)> = None;
+        let mut patches = sorter.into_stream_merger_iter()?;
+        while let Some((key, patch)) = patches.next()? {
+            let (external_id, _position) = key.split_at(key.len() - 8);
+            let patch = serde_json::from_slice(patch)?;
+
+            match current {
+                Some((ref id, ref mut document)) if id == external_id => {
+                    merge_patch(document, patch)
+                }
+                _ => {
+                    if let Some((id, document)) = current.take() {
+                        write_document(id, document)?;
+                    }
+
+                    let mut document = match external_documents_ids.get(external_id) {
+                        Some(id) => match self.documents(&txn, Some(id))?.into_iter().next() {
+                            Some((_, obkv)) => {
+                                let mut document =
+                                    milli::obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+                                // the vectors are merged like the other fields.
+                                if let Some(vectors) = vectors::get(self, &txn, id)? {
+                                    document.insert(VECTORS_FIELD.to_string(), vectors);
+                                }
+                                document
+                            }
+                            None => Map::new(),
+                        },
+                        None => Map::new(),
+                    };
+                    merge_patch(&mut document, patch);
+                    current = Some((external_id.to_vec(), document));
+                }
+            }
+        }
+        if let Some((id, document)) = current {
+            write_document(id, document)?;
+        }
+        batch.finish()?;
+
         let config = IndexDocumentsConfig {
-            update_method: method,
+            update_method: IndexDocumentsMethod::ReplaceDocuments,
             ..Default::default()
         };
 
@@ -288,17 +579,14 @@ impl Index {
             indexing_callback,
         )?;
 
-        for content_uuid in contents.into_iter() {
-            let content_file = file_store.get_update(content_uuid)?;
-            let reader = DocumentBatchReader::from_reader(content_file)?;
-            builder.add_documents(reader)?;
-        }
-
+        content.seek(SeekFrom::Start(0))?;
+        builder.add_documents(DocumentBatchReader::from_reader(content)?)?;
         let addition = builder.execute()?;
+        store_vectors(self, &mut txn, pending_vectors)?;
 
         txn.commit()?;
 
-        info!("document addition done: {:?}", addition);
+        info!("document merge done: {:?}", addition);
 
         Ok(addition)
     }
@@ -319,6 +607,144 @@ impl Index {
     }
 }
 
+/// Calls `f` on each document of the update file `content_uuid`.
+fn for_each_document(
+    file_store: &UpdateFileStore,
+    content_uuid: Uuid,
+    f: impl FnMut(Map<String, Value>) -> Result<()>,
+) -> Result<()> {
+    let content_file = file_store.get_update(content_uuid)?;
+    for_each_batch_document(DocumentBatchReader::from_reader(content_file)?, f)
+}
+
+/// Calls `f` on each document of `reader`.
+fn for_each_batch_document(
+    mut reader: DocumentBatchReader<File>,
+    mut f: impl FnMut(Map<String, Value>) -> Result<()>,
+) -> Result<()> {
+    while let Some((index, document)) = reader.next_document_with_index()? {
+        let mut object = Map::new();
+        for (field_id, content) in document.iter() {
+            if let Some(field_name) = index.name(field_id) {
+                object.insert(field_name.to_string(), serde_json::from_slice(content)?);
+            }
+        }
+        f(object)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the external id of `document`, if it has a valid one.
+fn external_id(primary_key: Option<&str>, document: &Map<String, Value>) -> Option<String> {
+    match document.get(primary_key?)? {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) if !id.is_f64() => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// The keys of the patches sorted for a merge are unique, there is never anything to merge.
+fn keep_first<'a>(
+    _key: &[u8],
+    values: &[Cow<'a, [u8]>],
+) -> std::result::Result<Cow<'a, [u8]>, Infallible> {
+    Ok(values[0].clone())
+}
+
+/// Applies `patch` to `target` following the JSON merge patch semantics (RFC 7396).
+fn merge_patch(target: &mut Map<String, Value>, patch: Map<String, Value>) {
+    for (key, value) in patch {
+        match value {
+            Value::Null => {
+                target.remove(&key);
+            }
+            Value::Object(patch) => match target.get_mut(&key) {
+                Some(Value::Object(inner)) => merge_patch(inner, patch),
+                _ => {
+                    let mut inner = Map::new();
+                    merge_patch(&mut inner, patch);
+                    target.insert(key, Value::Object(inner));
+                }
+            },
+            value => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The vectors taken out of the documents given to milli, with the external id of their document,
+/// in the order of the documents. `None` removes the vectors of a replaced document.
+pub(super) type PendingVectors = Vec<(String, Option<Value>)>;
+
+/// Takes the vectors out of the documents of `reader`, to be stored once milli gave an internal id to
+/// their documents. When `replace` is set, the documents without vectors remove the ones of the
+/// documents they replace. The documents are only written again when this is needed.
+pub(super) fn split_vectors(
+    reader: DocumentBatchReader<File>,
+    primary_key: &mut Option<String>,
+    replace: bool,
+    pending_vectors: &mut PendingVectors,
+) -> Result<DocumentBatchReader<File>> {
+    let with_vectors = reader.index().iter().any(|(_, name)| name == VECTORS_FIELD);
+    if !with_vectors && !replace {
+        return Ok(reader);
+    }
+
+    let mut content = tempfile::tempfile()?;
+    let mut batch = DocumentBatchBuilder::new(&mut content)?;
+    for_each_batch_document(reader, |mut document| {
+        // like milli, the primary key is inferred from the first document when the index has none.
+        if primary_key.is_none() {
+            *primary_key = document
+                .keys()
+                .find(|key| key.to_lowercase().contains("id"))
+                .cloned();
+        }
+
+        let vectors = document.remove(VECTORS_FIELD);
+        if vectors.is_some() || replace {
+            if let Some(external_id) = external_id(primary_key.as_deref(), &document) {
+                pending_vectors.push((external_id, vectors));
+            }
+        }
+        batch.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        Ok(())
+    })?;
+    batch.finish()?;
+
+    content.seek(SeekFrom::Start(0))?;
+    Ok(DocumentBatchReader::from_reader(content)?)
+}
+
+/// Stores the vectors taken out of the documents, now that milli indexed them.
+pub(super) fn store_vectors(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    pending_vectors: PendingVectors,
+) -> Result<()> {
+    if pending_vectors.is_empty() {
+        return Ok(());
+    }
+
+    let pending_vectors: Vec<_> = {
+        let external_documents_ids = index.external_documents_ids(txn)?;
+        pending_vectors
+            .into_iter()
+            .filter_map(|(external_id, vectors)| {
+                let id = external_documents_ids.get(external_id.as_bytes())?;
+                Some((id, vectors))
+            })
+            .collect()
+    };
+    for (id, vectors) in pending_vectors {
+        vectors::put(index, txn, id, &vectors.unwrap_or(Value::Null))?;
+    }
+
+    Ok(())
+}
+
 pub fn apply_settings_to_builder(
     settings: &Settings<Checked>,
     builder: &mut milli::update::Settings,
@@ -443,6 +869,162 @@ pub(crate) mod test {
         ]
     }
 
+    #[test]
+    fn test_document_edit_apply() {
+        let mut document = match serde_json::json!({
+            "id": 1,
+            "views": 10,
+            "rating": 4.5,
+            "tags": ["a"],
+            "stats": { "likes": 1 },
+        }) {
+            Value::Object(document) => document,
+            _ => unreachable!(),
+        };
+
+        let edits: Vec<DocumentEdit> = serde_json::from_value(serde_json::json!([
+            { "op": "increment", "path": "views", "by": 2 },
+            { "op": "increment", "path": "rating", "by": 0.25 },
+            { "op": "increment", "path": "stats.shares", "by": 1 },
+            { "op": "append", "path": "tags", "value": "b" },
+            { "op": "append", "path": "related", "value": 3 },
+            { "op": "set", "path": "stats.likes", "value": 5 },
+            { "op": "set", "path": "meta.author", "value": "kero" },
+            { "op": "remove", "path": "missing.field" },
+        ]))
+        .unwrap();
+        for edit in &edits {
+            edit.apply(&mut document).unwrap();
+        }
+
+        assert_eq!(
+            Value::Object(document.clone()),
+            serde_json::json!({
+                "id": 1,
+                "views": 12,
+                "rating": 4.75,
+                "tags": ["a", "b"],
+                "related": [3],
+                "stats": { "likes": 5, "shares": 1 },
+                "meta": { "author": "kero" },
+            })
+        );
+
+        let remove = DocumentEdit::Remove {
+            path: "stats.likes".to_string(),
+        };
+        assert!(remove.apply(&mut document).unwrap());
+        assert!(!remove.apply(&mut document).unwrap());
+        assert_eq!(document["stats"], serde_json::json!({ "shares": 1 }));
+
+        let set = DocumentEdit::Set {
+            path: "views".to_string(),
+            value: serde_json::json!(12),
+        };
+        assert!(!set.apply(&mut document).unwrap());
+
+        let increment = DocumentEdit::Increment {
+            path: "tags".to_string(),
+            by: Number::from(1),
+        };
+        assert!(matches!(
+            increment.apply(&mut document),
+            Err(IndexError::InvalidDocumentEdit(..))
+        ));
+
+        let nested = DocumentEdit::Set {
+            path: "views.count".to_string(),
+            value: serde_json::json!(1),
+        };
+        assert!(matches!(
+            nested.apply(&mut document),
+            Err(IndexError::InvalidDocumentEdit(..))
+        ));
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let mut document = match serde_json::json!({
+            "id": 1,
+            "title": "hello",
+            "price": { "amount": 10, "currency": "EUR" },
+            "tags": ["a", "b"],
+            "meta": 3,
+        }) {
+            Value::Object(document) => document,
+            _ => unreachable!(),
+        };
+        let patch = match serde_json::json!({
+            "id": 1,
+            "title": null,
+            "price": { "amount": 12, "discount": null },
+            "tags": ["c"],
+            "meta": { "author": "kero", "removed": null },
+        }) {
+            Value::Object(patch) => patch,
+            _ => unreachable!(),
+        };
+
+        merge_patch(&mut document, patch);
+
+        assert_eq!(
+            Value::Object(document),
+            serde_json::json!({
+                "id": 1,
+                "price": { "amount": 12, "currency": "EUR" },
+                "tags": ["c"],
+                "meta": { "author": "kero" },
+            })
+        );
+    }
+
+    #[test]
+    fn test_settings_merge() {
+        let mut settings = Settings::<Checked> {
+            searchable_attributes: Setting::Set(vec![String::from("title")]),
+            ranking_rules: Setting::Set(vec![String::from("words")]),
+            typo_tolerance: Setting::Reset,
+            ..Default::default()
+        };
+
+        settings.merge(Settings {
+            searchable_attributes: Setting::Reset,
+            filterable_attributes: Setting::Set(BTreeSet::from([String::from("genre")])),
+            typo_tolerance: Setting::Set(TypoSettings {
+                enabled: Setting::Set(false),
+                min_word_size_for_typos: Setting::Set(MinWordSizeTyposSetting {
+                    one_typo: Setting::Set(4),
+                    two_typos: Setting::NotSet,
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        assert_eq!(settings.searchable_attributes, Setting::Reset);
+        assert_eq!(
+            settings.filterable_attributes,
+            Setting::Set(BTreeSet::from([String::from("genre")]))
+        );
+        assert_eq!(
+            settings.ranking_rules,
+            Setting::Set(vec![String::from("words")])
+        );
+        // The fields that are not set in the later settings are still reset.
+        assert_eq!(
+            settings.typo_tolerance,
+            Setting::Set(TypoSettings {
+                enabled: Setting::Set(false),
+                min_word_size_for_typos: Setting::Set(MinWordSizeTyposSetting {
+                    one_typo: Setting::Set(4),
+                    two_typos: Setting::Reset,
+                }),
+                disable_on_words: Setting::Reset,
+                disable_on_attributes: Setting::Reset,
+            })
+        );
+    }
+
     #[test]
     fn test_setting_check() {
         // test no changes
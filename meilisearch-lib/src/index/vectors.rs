@@ -0,0 +1,74 @@
+use milli::heed::types::{DecodeIgnore, SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::DocumentId;
+use serde_json::Value;
+
+use super::error::Result;
+
+/// The name of the field holding the vectors of a document, either one vector or an array of
+/// vectors.
+pub const VECTORS_FIELD: &str = "_vectors";
+
+/// The vectors are taken out of the documents before they are given to milli, so that they are
+/// neither searchable nor displayed whatever the settings of the index. They are stored in the
+/// main database of the index instead, keyed by the internal id of their document.
+const VECTORS_KEY_PREFIX: &str = "meilisearch-vectors-";
+
+fn key(id: DocumentId) -> String {
+    format!("{}{}", VECTORS_KEY_PREFIX, id)
+}
+
+/// Returns the vectors of a document, if it has some.
+pub fn get(index: &milli::Index, rtxn: &RoTxn, id: DocumentId) -> Result<Option<Value>> {
+    Ok(index.main.get::<_, Str, SerdeJson<Value>>(rtxn, &key(id))?)
+}
+
+/// Sets the vectors of a document, `null` removes them.
+pub fn put(index: &milli::Index, wtxn: &mut RwTxn, id: DocumentId, vectors: &Value) -> Result<()> {
+    if vectors.is_null() {
+        index.main.delete::<_, Str>(wtxn, &key(id))?;
+    } else {
+        index
+            .main
+            .put::<_, Str, SerdeJson<Value>>(wtxn, &key(id), vectors)?;
+    }
+
+    Ok(())
+}
+
+/// Removes the vectors of the documents, which must be done when they are deleted since milli
+/// reuses their ids.
+pub fn delete(
+    index: &milli::Index,
+    wtxn: &mut RwTxn,
+    ids: impl IntoIterator<Item = DocumentId>,
+) -> Result<()> {
+    for id in ids {
+        index.main.delete::<_, Str>(wtxn, &key(id))?;
+    }
+
+    Ok(())
+}
+
+/// Removes the vectors of all the documents.
+pub fn clear(index: &milli::Index, wtxn: &mut RwTxn) -> Result<()> {
+    let keys = index
+        .main
+        .prefix_iter::<_, Str, DecodeIgnore>(wtxn, VECTORS_KEY_PREFIX)?
+        .map(|entry| entry.map(|(key, _)| key.to_string()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for key in keys {
+        index.main.delete::<_, Str>(wtxn, &key)?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether no document of the index has vectors.
+pub fn is_empty(index: &milli::Index, rtxn: &RoTxn) -> Result<bool> {
+    Ok(index
+        .main
+        .prefix_iter::<_, Str, DecodeIgnore>(rtxn, VECTORS_KEY_PREFIX)?
+        .next()
+        .is_none())
+}
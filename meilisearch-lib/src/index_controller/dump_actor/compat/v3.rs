@@ -75,6 +75,7 @@ impl From<Update> for TaskContent {
                 // document count is unknown for legacy updates
                 documents_count: 0,
                 allow_index_creation: true,
+                deep_merge: false,
             },
             Update::Settings(settings) => TaskContent::SettingsUpdate {
                 settings,
@@ -190,6 +191,7 @@ impl From<(UpdateStatus, String, TaskId)> for Task {
             index_uid: IndexUid::new(uid).unwrap(),
             content: TaskContent::IndexDeletion,
             events: Vec::new(),
+            priority: Default::default(),
         };
 
         match update {
@@ -406,7 +406,7 @@ mod test {
     use crate::options::SchedulerConfig;
     use crate::tasks::error::Result as TaskResult;
     use crate::tasks::task::{Task, TaskId};
-    use crate::tasks::{MockTaskPerformer, TaskFilter, TaskStore};
+    use crate::tasks::{MockTaskPerformer, TaskStore};
     use crate::update_file_store::UpdateFileStore;
 
     fn setup() {
@@ -448,9 +448,7 @@ mod test {
             .when::<(&Path, UpdateFileStore), TaskResult<()>>("dump")
             .then(|_| Ok(()));
         mocker
-            .when::<(Option<TaskId>, Option<TaskFilter>, Option<usize>), TaskResult<Vec<Task>>>(
-                "list_tasks",
-            )
+            .when::<Option<TaskId>, TaskResult<Vec<Task>>>("fetch_unfinished_tasks")
             .then(|_| Ok(Vec::new()));
         let store = TaskStore::mock(mocker);
         let config = SchedulerConfig::default();
@@ -480,9 +478,7 @@ mod test {
 
         let mocker = Mocker::default();
         mocker
-            .when::<(Option<TaskId>, Option<TaskFilter>, Option<usize>), TaskResult<Vec<Task>>>(
-                "list_tasks",
-            )
+            .when::<Option<TaskId>, TaskResult<Vec<Task>>>("fetch_unfinished_tasks")
             .then(|_| Ok(Vec::new()));
         let task_store = TaskStore::mock(mocker);
         let mut performer = MockTaskPerformer::new();
@@ -12,22 +12,27 @@ use futures::Stream;
 use futures::StreamExt;
 use milli::update::IndexDocumentsMethod;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use time::OffsetDateTime;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 use uuid::Uuid;
 
 use crate::document_formats::{read_csv, read_json, read_ndjson};
 use crate::index::{
-    Checked, Document, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings, Unchecked,
+    BrowseQuery, BrowseResult, Checked, Document, DocumentEdit, FacetSearchQuery,
+    FacetSearchResult, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings, Unchecked,
 };
 use crate::index_controller::dump_actor::{load_dump, DumpActor, DumpActorHandleImpl};
 use crate::options::{IndexerOpts, SchedulerConfig};
 use crate::snapshot::{load_snapshot, SnapshotService};
 use crate::tasks::error::TaskError;
-use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskId};
-use crate::tasks::{Scheduler, TaskFilter, TaskStore};
+use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskId, TaskPriority};
+use crate::tasks::{
+    Scheduler, TaskFilter, TaskRetentionService, TaskStore, TaskWebhook, WebhookPayload,
+};
 use error::Result;
 
 use self::dump_actor::{DumpActorHandle, DumpInfo};
@@ -124,6 +129,11 @@ pub struct Stats {
 #[derivative(Debug)]
 pub enum Update {
     DeleteDocuments(Vec<String>),
+    DeleteDocumentsByFilter(Value),
+    EditDocuments {
+        filter: Option<Value>,
+        edits: Vec<DocumentEdit>,
+    },
     ClearDocuments,
     Settings {
         settings: Settings<Unchecked>,
@@ -138,6 +148,8 @@ pub enum Update {
         method: IndexDocumentsMethod,
         format: DocumentAdditionFormat,
         allow_index_creation: bool,
+        /// Whether the documents are deep merged into the existing ones, as a JSON merge patch.
+        deep_merge: bool,
     },
     DeleteIndex,
     CreateIndex {
@@ -162,6 +174,7 @@ pub struct IndexControllerBuilder {
     dump_dst: Option<PathBuf>,
     ignore_dump_if_db_exists: bool,
     ignore_missing_dump: bool,
+    task_webhook_payload: Option<WebhookPayload>,
 }
 
 impl IndexControllerBuilder {
@@ -220,7 +233,26 @@ impl IndexControllerBuilder {
             update_file_store.clone(),
         )?);
 
-        let task_store = TaskStore::new(meta_env)?;
+        let mut task_store = TaskStore::new(meta_env)?;
+        if let Some(ref url) = scheduler_config.task_webhook_url {
+            let payload = self
+                .task_webhook_payload
+                .unwrap_or(|task| serde_json::json!(task));
+            let webhook = TaskWebhook::spawn(
+                url.clone(),
+                scheduler_config.task_webhook_authorization_header.clone(),
+                payload,
+            )?;
+            task_store.set_webhook(webhook);
+        }
+
+        task_store.recover_interrupted_tasks(&update_file_store)?;
+
+        let retention_max_count = scheduler_config.task_retention_max_count;
+        let retention_max_age = scheduler_config
+            .task_retention_max_age_days
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60));
+
         let scheduler =
             Scheduler::new(task_store.clone(), index_resolver.clone(), scheduler_config)?;
 
@@ -265,6 +297,17 @@ impl IndexControllerBuilder {
             tokio::task::spawn_local(snapshot_service.run());
         }
 
+        if retention_max_count.is_some() || retention_max_age.is_some() {
+            let retention_service = TaskRetentionService {
+                task_store: task_store.clone(),
+                update_file_store: update_file_store.clone(),
+                max_count: retention_max_count,
+                max_age: retention_max_age,
+            };
+
+            tokio::task::spawn_local(retention_service.run());
+        }
+
         Ok(IndexController {
             index_resolver,
             scheduler,
@@ -274,6 +317,13 @@ impl IndexControllerBuilder {
         })
     }
 
+    /// Set the function building the body of the task webhook notifications. By default, the tasks
+    /// are sent as they are stored.
+    pub fn set_task_webhook_payload(&mut self, payload: WebhookPayload) -> &mut Self {
+        self.task_webhook_payload.replace(payload);
+        self
+    }
+
     /// Set the index controller builder's max update store size.
     pub fn set_max_task_store_size(&mut self, max_update_store_size: usize) -> &mut Self {
         self.max_task_store_size.replace(max_update_store_size);
@@ -358,12 +408,25 @@ where
         IndexControllerBuilder::default()
     }
 
-    pub async fn register_update(&self, uid: String, update: Update) -> Result<Task> {
+    /// Registers `update` as a new task on the index `uid`. The task has a normal priority when
+    /// `priority` is unspecified.
+    pub async fn register_update(
+        &self,
+        uid: String,
+        update: Update,
+        priority: Option<TaskPriority>,
+    ) -> Result<Task> {
         let uid = IndexUid::new(uid)?;
         let content = match update {
             Update::DeleteDocuments(ids) => {
                 TaskContent::DocumentDeletion(DocumentDeletion::Ids(ids))
             }
+            Update::DeleteDocumentsByFilter(filter) => {
+                TaskContent::DocumentDeletion(DocumentDeletion::Filter(filter))
+            }
+            Update::EditDocuments { filter, edits } => {
+                TaskContent::DocumentEdition { filter, edits }
+            }
             Update::ClearDocuments => TaskContent::DocumentDeletion(DocumentDeletion::Clear),
             Update::Settings {
                 settings,
@@ -380,6 +443,7 @@ where
                 format,
                 method,
                 allow_index_creation,
+                deep_merge,
             } => {
                 let mut buffer = Vec::new();
                 while let Some(bytes) = payload.next().await {
@@ -412,6 +476,7 @@ where
                     primary_key,
                     documents_count,
                     allow_index_creation,
+                    deep_merge,
                 }
             }
             Update::DeleteIndex => TaskContent::IndexDeletion,
@@ -419,8 +484,13 @@ where
             Update::UpdateIndex { primary_key } => TaskContent::IndexUpdate { primary_key },
         };
 
-        let task = self.task_store.register(uid, content).await?;
-        self.scheduler.read().await.notify();
+        let task = self
+            .task_store
+            .register(uid, content, priority.unwrap_or_default())
+            .await?;
+        let scheduler = self.scheduler.read().await;
+        scheduler.publish(&task);
+        scheduler.notify();
 
         Ok(task)
     }
@@ -430,6 +500,77 @@ where
         Ok(task)
     }
 
+    /// Returns a receiver of the tasks, sent each time a new event is pushed to one of them.
+    pub async fn subscribe_to_tasks(&self) -> broadcast::Receiver<Task> {
+        self.scheduler.read().await.subscribe()
+    }
+
+    /// Waits at most `timeout` for the task `id` to finish, and returns it.
+    pub async fn wait_task(&self, id: TaskId, timeout: Duration) -> Result<Task> {
+        let receiver = self.subscribe_to_tasks().await;
+
+        tokio::time::timeout(timeout, self.wait_task_finished(id, receiver))
+            .await
+            .map_err(|_| TaskError::WaitTimeout(id))?
+    }
+
+    /// Waits for the task `id` to finish, listening to the task updates sent to `receiver`.
+    async fn wait_task_finished(
+        &self,
+        id: TaskId,
+        mut receiver: broadcast::Receiver<Task>,
+    ) -> Result<Task> {
+        // The task may have finished before we subscribed to the updates.
+        let task = self.get_task(id, None).await?;
+        if task.is_finished() {
+            return Ok(task);
+        }
+
+        loop {
+            match receiver.recv().await {
+                Ok(task) if task.id == id && task.is_finished() => return Ok(task),
+                Ok(_) => (),
+                // Some updates were missed, maybe including the one we are waiting for.
+                Err(RecvError::Lagged(_)) => {
+                    let task = self.get_task(id, None).await?;
+                    if task.is_finished() {
+                        return Ok(task);
+                    }
+                }
+                // The scheduler is gone, the task won't make any progress.
+                Err(RecvError::Closed) => futures::future::pending().await,
+            }
+        }
+    }
+
+    /// Cancels the enqueued task `id` and deletes its payload, if any.
+    pub async fn cancel_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
+        let task = self.scheduler.write().await.cancel_task(id, filter).await?;
+
+        if let Some(content_uuid) = task.get_content_uuid() {
+            if let Err(e) = self.update_file_store.delete(content_uuid).await {
+                log::error!("error deleting update file: {}", e);
+            }
+        }
+
+        Ok(task)
+    }
+
+    /// Deletes the finished tasks matching `filter`, from the task id `from` down to the oldest
+    /// task, and returns how many were deleted.
+    pub async fn delete_tasks(
+        &self,
+        filter: Option<TaskFilter>,
+        from: Option<TaskId>,
+    ) -> Result<usize> {
+        let deleted = self
+            .task_store
+            .delete_tasks(from, filter, self.update_file_store.clone())
+            .await?;
+
+        Ok(deleted)
+    }
+
     pub async fn get_index_task(&self, index_uid: String, task_id: TaskId) -> Result<Task> {
         let creation_task_id = self
             .index_resolver
@@ -451,17 +592,19 @@ where
         Ok(task)
     }
 
+    /// Lists at most `limit` tasks matching `filter`, from the task id `from` down to the oldest
+    /// task.
     pub async fn list_tasks(
         &self,
         filter: Option<TaskFilter>,
         limit: Option<usize>,
-        offset: Option<TaskId>,
+        from: Option<TaskId>,
     ) -> Result<Vec<Task>> {
         let tasks = self
             .scheduler
             .read()
             .await
-            .list_tasks(offset, filter, limit)
+            .list_tasks(from, filter, limit)
             .await?;
 
         Ok(tasks)
@@ -471,7 +614,7 @@ where
         &self,
         index_uid: String,
         limit: Option<usize>,
-        offset: Option<TaskId>,
+        from: Option<TaskId>,
     ) -> Result<Vec<Task>> {
         let task_id = self
             .index_resolver
@@ -480,16 +623,14 @@ where
 
         let mut filter = TaskFilter::default();
         filter.filter_index(index_uid);
+        // The tasks of a previously deleted index with the same uid must not be listed.
+        filter.filter_fn(move |task| task.id >= task_id);
 
         let tasks = self
             .scheduler
             .read()
             .await
-            .list_tasks(
-                Some(offset.unwrap_or_default() + task_id),
-                Some(filter),
-                limit,
-            )
+            .list_tasks(from, Some(filter), limit)
             .await?;
 
         Ok(tasks)
@@ -524,14 +665,22 @@ where
         offset: usize,
         limit: usize,
         attributes_to_retrieve: Option<Vec<String>>,
+        retrieve_vectors: bool,
     ) -> Result<Vec<Document>> {
         let index = self.index_resolver.get_index(uid).await?;
-        let documents =
-            spawn_blocking(move || index.retrieve_documents(offset, limit, attributes_to_retrieve))
-                .await??;
+        let documents = spawn_blocking(move || {
+            index.retrieve_documents(offset, limit, attributes_to_retrieve, retrieve_vectors)
+        })
+        .await??;
         Ok(documents)
     }
 
+    pub async fn browse_documents(&self, uid: String, query: BrowseQuery) -> Result<BrowseResult> {
+        let index = self.index_resolver.get_index(uid).await?;
+        let result = spawn_blocking(move || index.browse_documents(query)).await??;
+        Ok(result)
+    }
+
     pub async fn document(
         &self,
         uid: String,
@@ -551,6 +700,16 @@ where
         Ok(result)
     }
 
+    pub async fn facet_search(
+        &self,
+        uid: String,
+        query: FacetSearchQuery,
+    ) -> Result<FacetSearchResult> {
+        let index = self.index_resolver.get_index(uid).await?;
+        let result = spawn_blocking(move || index.perform_facet_search(query)).await??;
+        Ok(result)
+    }
+
     pub async fn get_index(&self, uid: String) -> Result<IndexMetadata> {
         let index = self.index_resolver.get_index(uid.clone()).await?;
         let uuid = index.uuid();
@@ -566,11 +725,10 @@ where
 
     pub async fn get_index_stats(&self, uid: String) -> Result<IndexStats> {
         let processing_tasks = self.scheduler.read().await.get_processing_tasks().await?;
-        // Check if the currently indexing update is from our index.
+        // Check if one of the currently indexing updates is from our index.
         let is_indexing = processing_tasks
-            .first()
-            .map(|task| task.index_uid.as_str() == uid)
-            .unwrap_or_default();
+            .iter()
+            .any(|task| task.index_uid.as_str() == uid);
 
         let index = self.index_resolver.get_index(uid).await?;
         let mut stats = spawn_blocking(move || index.stats()).await??;
@@ -602,11 +760,12 @@ where
                 Some(last.max(meta.updated_at))
             });
 
-            // Check if the currently indexing update is from our index.
-            stats.is_indexing = processing_tasks
-                .first()
-                .map(|p| p.index_uid.as_str() == index_uid)
-                .or(Some(false));
+            // Check if one of the currently indexing updates is from our index.
+            stats.is_indexing = Some(
+                processing_tasks
+                    .iter()
+                    .any(|p| p.index_uid.as_str() == index_uid),
+            );
 
             indexes.insert(index_uid, stats);
         }
@@ -650,6 +809,7 @@ mod test {
     use crate::index::Index;
     use crate::index::{
         default_crop_marker, default_highlight_post_tag, default_highlight_pre_tag,
+        DEFAULT_SEMANTIC_RATIO,
     };
     use crate::index_resolver::index_store::MockIndexStore;
     use crate::index_resolver::meta_store::MockIndexMetaStore;
@@ -694,6 +854,15 @@ mod test {
             highlight_pre_tag: default_highlight_pre_tag(),
             highlight_post_tag: default_highlight_post_tag(),
             crop_marker: default_crop_marker(),
+            cursor: None,
+            exhaustive_counts: false,
+            attributes_to_search_on: None,
+            ranking_rules: None,
+            show_ranking_score: false,
+            show_ranking_score_details: false,
+            matching_strategy: Default::default(),
+            vector: None,
+            semantic_ratio: DEFAULT_SEMANTIC_RATIO,
         };
 
         let result = SearchResult {
@@ -706,6 +875,8 @@ mod test {
             processing_time_ms: 50,
             facets_distribution: None,
             exhaustive_facets_count: Some(true),
+            facet_stats: None,
+            next_cursor: None,
         };
 
         let mut uuid_store = MockIndexMetaStore::new();
@@ -11,14 +11,14 @@ use index_store::{IndexStore, MapIndexStore};
 use meilisearch_error::ResponseError;
 use meta_store::{HeedMetaStore, IndexMetaStore};
 use milli::heed::Env;
-use milli::update::{DocumentDeletionResult, IndexerConfig};
+use milli::update::{DocumentAdditionResult, DocumentDeletionResult, IndexerConfig};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tokio::sync::oneshot;
 use tokio::task::spawn_blocking;
 use uuid::Uuid;
 
-use crate::index::{error::Result as IndexResult, Index};
+use crate::index::{error::Result as IndexResult, Checked, Index, Settings};
 use crate::options::IndexerOpts;
 use crate::tasks::batch::Batch;
 use crate::tasks::task::{DocumentDeletion, Job, Task, TaskContent, TaskEvent, TaskId, TaskResult};
@@ -98,40 +98,51 @@ where
     I: IndexStore + Send + Sync + 'static,
 {
     async fn process_batch(&self, mut batch: Batch) -> Batch {
-        // If a batch contains multiple tasks, then it must be a document addition batch
-        if let Some(Task {
-            content: TaskContent::DocumentAddition { .. },
-            ..
-        }) = batch.tasks.first()
-        {
-            debug_assert!(batch.tasks.iter().all(|t| matches!(
-                t,
-                Task {
-                    content: TaskContent::DocumentAddition { .. },
-                    ..
-                }
-            )));
+        // If a batch contains multiple tasks, then it must be a document addition, a settings
+        // update or a document deletion batch, that is processed in a single transaction.
+        match batch.tasks.first() {
+            Some(Task {
+                content: TaskContent::DocumentAddition { .. },
+                ..
+            }) => {
+                debug_assert!(batch.tasks.iter().all(|t| matches!(
+                    t,
+                    Task {
+                        content: TaskContent::DocumentAddition { .. },
+                        ..
+                    }
+                )));
 
-            self.process_document_addition_batch(batch).await
-        } else {
-            if let Some(task) = batch.tasks.first_mut() {
-                task.events
-                    .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
-
-                match self.process_task(task).await {
-                    Ok(success) => {
-                        task.events.push(TaskEvent::Succeded {
-                            result: success,
+                self.process_document_addition_batch(batch).await
+            }
+            Some(Task {
+                content: TaskContent::SettingsUpdate { .. },
+                ..
+            }) => self.process_settings_batch(batch).await,
+            Some(Task {
+                content: TaskContent::DocumentDeletion(DocumentDeletion::Ids(_)),
+                ..
+            }) => self.process_document_deletion_batch(batch).await,
+            _ => {
+                if let Some(task) = batch.tasks.first_mut() {
+                    task.events
+                        .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
+
+                    match self.process_task(task).await {
+                        Ok(success) => {
+                            task.events.push(TaskEvent::Succeded {
+                                result: success,
+                                timestamp: OffsetDateTime::now_utc(),
+                            });
+                        }
+                        Err(err) => task.events.push(TaskEvent::Failed {
+                            error: err.into(),
                             timestamp: OffsetDateTime::now_utc(),
-                        });
+                        }),
                     }
-                    Err(err) => task.events.push(TaskEvent::Failed {
-                        error: err.into(),
-                        timestamp: OffsetDateTime::now_utc(),
-                    }),
                 }
+                batch
             }
-            batch
         }
     }
 
@@ -211,12 +222,14 @@ where
                         merge_strategy,
                         primary_key,
                         allow_index_creation,
+                        deep_merge,
                         ..
                     },
                 ..
             }) => {
                 let primary_key = primary_key.clone();
                 let method = *merge_strategy;
+                let deep_merge = *deep_merge;
 
                 let index = if *allow_index_creation {
                     self.get_or_create_index(index_uid.clone(), *id).await
@@ -243,12 +256,16 @@ where
 
                 let file_store = self.file_store.clone();
                 let result = spawn_blocking(move || {
-                    index.update_documents(
-                        method,
-                        primary_key,
-                        file_store,
-                        content_uuids.into_iter(),
-                    )
+                    if deep_merge {
+                        index.merge_documents(primary_key, file_store, content_uuids.into_iter())
+                    } else {
+                        index.update_documents(
+                            method,
+                            primary_key,
+                            file_store,
+                            content_uuids.into_iter(),
+                        )
+                    }
                 })
                 .await;
 
@@ -279,6 +296,111 @@ where
         }
     }
 
+    /// Merges the settings of the batch, in order, and applies them in a single update. The
+    /// index is fetched or created as the first task of the batch allows it.
+    async fn process_settings_batch(&self, mut batch: Batch) -> Batch {
+        let mut merged = Settings::<Checked>::default();
+        for task in &batch.tasks {
+            match &task.content {
+                TaskContent::SettingsUpdate { settings, .. } => {
+                    merged.merge(settings.clone().check())
+                }
+                _ => panic!("unexpected task in the settings batch"),
+            }
+        }
+
+        let result = match batch.tasks.first() {
+            Some(Task {
+                index_uid,
+                id,
+                content:
+                    TaskContent::SettingsUpdate {
+                        is_deletion,
+                        allow_index_creation,
+                        ..
+                    },
+                ..
+            }) => {
+                async {
+                    let index = if *is_deletion || !*allow_index_creation {
+                        self.get_index(index_uid.as_str().to_string()).await?
+                    } else {
+                        self.get_or_create_index(index_uid.clone(), *id).await?
+                    };
+
+                    spawn_blocking(move || index.update_settings(&merged)).await??;
+
+                    Result::Ok(())
+                }
+                .await
+            }
+            _ => panic!("invalid batch!"),
+        };
+
+        let event = match result {
+            Ok(()) => TaskEvent::Succeded {
+                result: TaskResult::Other,
+                timestamp: OffsetDateTime::now_utc(),
+            },
+            Err(e) => TaskEvent::Failed {
+                error: e.into(),
+                timestamp: OffsetDateTime::now_utc(),
+            },
+        };
+
+        for task in batch.tasks.iter_mut() {
+            task.events.push(event.clone());
+        }
+
+        batch
+    }
+
+    /// Deletes the ids of all the tasks of the batch in a single deletion. As for document
+    /// additions, every task reports the number of documents deleted by the whole batch.
+    async fn process_document_deletion_batch(&self, mut batch: Batch) -> Batch {
+        let mut ids = Vec::new();
+        for task in &batch.tasks {
+            match &task.content {
+                TaskContent::DocumentDeletion(DocumentDeletion::Ids(task_ids)) => {
+                    ids.extend_from_slice(task_ids)
+                }
+                _ => panic!("unexpected task in the document deletion batch"),
+            }
+        }
+
+        let index_uid = match batch.tasks.first() {
+            Some(task) => task.index_uid.as_str().to_string(),
+            None => panic!("invalid batch!"),
+        };
+
+        let result = async {
+            let index = self.get_index(index_uid).await?;
+            let result = spawn_blocking(move || index.delete_documents(&ids)).await??;
+
+            Result::Ok(result)
+        }
+        .await;
+
+        let event = match result {
+            Ok(DocumentDeletionResult {
+                deleted_documents, ..
+            }) => TaskEvent::Succeded {
+                result: TaskResult::DocumentDeletion { deleted_documents },
+                timestamp: OffsetDateTime::now_utc(),
+            },
+            Err(e) => TaskEvent::Failed {
+                error: e.into(),
+                timestamp: OffsetDateTime::now_utc(),
+            },
+        };
+
+        for task in batch.tasks.iter_mut() {
+            task.events.push(event.clone());
+        }
+
+        batch
+    }
+
     async fn process_task(&self, task: &Task) -> Result<TaskResult> {
         let index_uid = task.index_uid.clone();
         match &task.content {
@@ -293,6 +415,29 @@ where
 
                 Ok(TaskResult::DocumentDeletion { deleted_documents })
             }
+            TaskContent::DocumentDeletion(DocumentDeletion::Filter(filter)) => {
+                let filter = filter.clone();
+                let index = self.get_index(index_uid.into_inner()).await?;
+
+                let DocumentDeletionResult {
+                    deleted_documents, ..
+                } = spawn_blocking(move || index.delete_documents_by_filter(&filter)).await??;
+
+                Ok(TaskResult::DocumentDeletion { deleted_documents })
+            }
+            TaskContent::DocumentEdition { filter, edits } => {
+                let filter = filter.clone();
+                let edits = edits.clone();
+                let index = self.get_index(index_uid.into_inner()).await?;
+
+                let DocumentAdditionResult {
+                    indexed_documents, ..
+                } = spawn_blocking(move || index.edit_documents(filter.as_ref(), &edits)).await??;
+
+                Ok(TaskResult::DocumentEdition {
+                    edited_documents: indexed_documents,
+                })
+            }
             TaskContent::DocumentDeletion(DocumentDeletion::Clear) => {
                 let index = self.get_index(index_uid.into_inner()).await?;
                 let deleted_documents = spawn_blocking(move || -> IndexResult<u64> {
@@ -489,13 +634,14 @@ mod test {
     use super::*;
 
     use futures::future::ok;
-    use milli::update::{DocumentAdditionResult, IndexDocumentsMethod};
+    use milli::update::IndexDocumentsMethod;
     use nelson::Mocker;
     use proptest::prelude::*;
+    use serde_json::Value;
 
     use crate::index::{
         error::{IndexError, Result as IndexResult},
-        Checked, IndexMeta, IndexStats, Settings,
+        Checked, DocumentEdit, IndexMeta, IndexStats, Settings,
     };
     use index_store::MockIndexStore;
     use meta_store::MockIndexMetaStore;
@@ -516,7 +662,7 @@ mod test {
 
                 // Return arbitrary data from index call.
                 match &task.content {
-                    TaskContent::DocumentAddition{primary_key, ..} => {
+                    TaskContent::DocumentAddition{primary_key, deep_merge, ..} => {
                         let result = move || if !index_op_fails {
                             Ok(DocumentAdditionResult { indexed_documents: any_int, number_of_documents: any_int })
                         } else {
@@ -527,8 +673,13 @@ mod test {
                             mocker.when::<String, IndexResult<IndexMeta>>("update_primary_key")
                                 .then(move |_| Ok(IndexMeta{ created_at: OffsetDateTime::now_utc(), updated_at: OffsetDateTime::now_utc(), primary_key: None }));
                         }
-                        mocker.when::<(IndexDocumentsMethod, Option<String>, UpdateFileStore, IntoIter<Uuid>), IndexResult<DocumentAdditionResult>>("update_documents")
-                                .then(move |(_, _, _, _)| result());
+                        if *deep_merge {
+                            mocker.when::<(Option<String>, UpdateFileStore, IntoIter<Uuid>), IndexResult<DocumentAdditionResult>>("merge_documents")
+                                    .then(move |(_, _, _)| result());
+                        } else {
+                            mocker.when::<(IndexDocumentsMethod, Option<String>, UpdateFileStore, IntoIter<Uuid>), IndexResult<DocumentAdditionResult>>("update_documents")
+                                    .then(move |(_, _, _, _)| result());
+                        }
                     }
                     TaskContent::SettingsUpdate{..} => {
                         let result = move || if !index_op_fails {
@@ -551,6 +702,28 @@ mod test {
                         mocker.when::<&[String], IndexResult<DocumentDeletionResult>>("delete_documents")
                                 .then(move |_| result());
                     },
+                    TaskContent::DocumentDeletion(DocumentDeletion::Filter(_filter)) => {
+                        let result = move || if !index_op_fails {
+                            Ok(DocumentDeletionResult { deleted_documents: any_int as u64, remaining_documents: any_int as u64 })
+                        } else {
+                            // return this error because it's easy to generate...
+                            Err(IndexError::DocumentNotFound("a doc".into()))
+                        };
+
+                        mocker.when::<&Value, IndexResult<DocumentDeletionResult>>("delete_documents_by_filter")
+                                .then(move |_| result());
+                    },
+                    TaskContent::DocumentEdition { .. } => {
+                        let result = move || if !index_op_fails {
+                            Ok(DocumentAdditionResult { indexed_documents: any_int, number_of_documents: any_int })
+                        } else {
+                            // return this error because it's easy to generate...
+                            Err(IndexError::DocumentNotFound("a doc".into()))
+                        };
+
+                        mocker.when::<(Option<&Value>, &[DocumentEdit]), IndexResult<DocumentAdditionResult>>("edit_documents")
+                                .then(move |_| result());
+                    },
                     TaskContent::DocumentDeletion(DocumentDeletion::Clear) => {
                         let result = move || if !index_op_fails {
                             Ok(())
@@ -651,6 +824,7 @@ mod test {
                     || (index_exists && matches!(task.content, TaskContent::IndexCreation { .. }))
                     || (!index_exists && matches!(task.content, TaskContent::IndexDeletion
                                                                 | TaskContent::DocumentDeletion(_)
+                                                                | TaskContent::DocumentEdition { .. }
                                                                 | TaskContent::SettingsUpdate { is_deletion: true, ..}
                                                                 | TaskContent::SettingsUpdate { allow_index_creation: false, ..}
                                                                 | TaskContent::DocumentAddition { allow_index_creation: false, ..}
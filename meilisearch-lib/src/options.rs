@@ -41,27 +41,67 @@ pub struct IndexerOpts {
 
 #[derive(Debug, Clone, Parser, Default, Serialize)]
 pub struct SchedulerConfig {
-    /// enable the autobatching experimental feature
-    #[clap(long, hide = true)]
+    /// Enables the auto-batching of the tasks: consecutive document additions, document deletions
+    /// by id or settings updates on the same index are processed together, in a single
+    /// transaction.
+    #[clap(long, env = "MEILI_ENABLE_AUTO_BATCHING")]
     pub enable_auto_batching: bool,
 
-    // The maximum number of updates of the same type that can be batched together.
-    // If unspecified, this is unlimited. A value of 0 is interpreted as 1.
-    #[clap(long, requires = "enable-auto-batching", hide = true)]
+    /// The maximum number of updates of the same type that can be batched together.
+    /// If unspecified, this is unlimited. A value of 0 is interpreted as 1.
+    #[clap(long, env = "MEILI_MAX_BATCH_SIZE", requires = "enable-auto-batching")]
     pub max_batch_size: Option<usize>,
 
-    // The maximum number of documents in a document batch. Since batches must contain at least one
-    // update for the scheduler to make progress, the number of documents in a batch will be at
-    // least the number of documents of its first update.
-    #[clap(long, requires = "enable-auto-batching", hide = true)]
+    /// The maximum number of documents in a document batch. Since batches must contain at least one
+    /// update for the scheduler to make progress, the number of documents in a batch will be at
+    /// least the number of documents of its first update.
+    #[clap(
+        long,
+        env = "MEILI_MAX_DOCUMENTS_PER_BATCH",
+        requires = "enable-auto-batching"
+    )]
     pub max_documents_per_batch: Option<usize>,
 
     /// Debounce duration in seconds
     ///
     /// When a new task is enqueued, the scheduler waits for `debounce_duration_sec` seconds for new updates before
     /// starting to process a batch of updates.
-    #[clap(long, requires = "enable-auto-batching", hide = true)]
+    #[clap(
+        long,
+        env = "MEILI_DEBOUNCE_DURATION_SEC",
+        requires = "enable-auto-batching"
+    )]
     pub debounce_duration_sec: Option<u64>,
+
+    /// The maximum number of batches processed concurrently. The batches processed concurrently
+    /// always belong to different indexes. Defaults to 1.
+    #[clap(long, env = "MEILI_BATCH_WORKERS")]
+    pub batch_workers: Option<usize>,
+
+    /// The number of most recent tasks to keep in the task store. Older finished tasks are
+    /// periodically deleted. If unspecified, finished tasks are never deleted based on their number.
+    #[clap(long, env = "MEILI_TASK_RETENTION_MAX_COUNT")]
+    pub task_retention_max_count: Option<u64>,
+
+    /// The number of days during which finished tasks are kept in the task store. Tasks that
+    /// finished earlier are periodically deleted. If unspecified, finished tasks are never deleted
+    /// based on their age.
+    #[clap(long, env = "MEILI_TASK_RETENTION_MAX_AGE_DAYS")]
+    pub task_retention_max_age_days: Option<u64>,
+
+    /// The URL to which the tasks are posted when they succeed or fail.
+    #[serde(skip)]
+    #[clap(long, env = "MEILI_TASK_WEBHOOK_URL")]
+    pub task_webhook_url: Option<String>,
+
+    /// The value of the `Authorization` header sent along with the task webhook notifications.
+    #[serde(skip)]
+    #[clap(
+        long,
+        env = "MEILI_TASK_WEBHOOK_AUTHORIZATION_HEADER",
+        requires = "task-webhook-url"
+    )]
+    pub task_webhook_authorization_header: Option<String>,
 }
 
 impl TryFrom<&IndexerOpts> for IndexerConfig {
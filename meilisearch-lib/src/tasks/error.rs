@@ -11,6 +11,12 @@ pub type Result<T> = std::result::Result<T, TaskError>;
 pub enum TaskError {
     #[error("Task `{0}` not found.")]
     UnexistingTask(TaskId),
+    #[error("Task `{0}` cannot be canceled because it is already processing or finished.")]
+    TaskNotCancelable(TaskId),
+    #[error("Task `{0}` did not finish before the timeout. Its status can still be retrieved on the tasks routes.")]
+    WaitTimeout(TaskId),
+    #[error("Task `{0}` was interrupted while processing and cannot be safely processed again.")]
+    Interrupted(TaskId),
     #[error("Internal error: {0}")]
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
 }
@@ -27,6 +33,9 @@ impl ErrorCode for TaskError {
     fn error_code(&self) -> Code {
         match self {
             TaskError::UnexistingTask(_) => Code::TaskNotFound,
+            TaskError::TaskNotCancelable(_) => Code::TaskNotCancelable,
+            TaskError::WaitTimeout(_) => Code::TaskWaitTimeout,
+            TaskError::Interrupted(_) => Code::TaskInterrupted,
             TaskError::Internal(_) => Code::Internal,
         }
     }
@@ -1,7 +1,9 @@
 use async_trait::async_trait;
 
+pub use retention::TaskRetentionService;
 pub use scheduler::Scheduler;
 pub use task_store::TaskFilter;
+pub use webhook::{TaskWebhook, WebhookPayload};
 
 #[cfg(test)]
 pub use task_store::test::MockTaskStore as TaskStore;
@@ -15,10 +17,12 @@ use self::task::Job;
 
 pub mod batch;
 pub mod error;
+mod retention;
 mod scheduler;
 pub mod task;
 mod task_store;
 pub mod update_loop;
+mod webhook;
 
 #[cfg_attr(test, mockall::automock(type Error=test::DebugError;))]
 #[async_trait]
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use log::{error, info};
+use time::OffsetDateTime;
+use tokio::time::sleep;
+
+use super::error::Result;
+use super::{TaskFilter, TaskStore};
+use crate::update_file_store::UpdateFileStore;
+
+/// Interval between two applications of the retention policy.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Periodically deletes the finished tasks that fall out of the retention policy.
+pub struct TaskRetentionService {
+    pub(crate) task_store: TaskStore,
+    pub(crate) update_file_store: UpdateFileStore,
+    /// The number of most recent tasks to keep.
+    pub(crate) max_count: Option<u64>,
+    /// The maximum age of a finished task.
+    pub(crate) max_age: Option<Duration>,
+}
+
+impl TaskRetentionService {
+    pub async fn run(self) {
+        loop {
+            match self.prune().await {
+                Ok(0) => (),
+                Ok(deleted) => info!("Deleted {} tasks according to retention policy.", deleted),
+                Err(e) => error!("Error while applying the task retention policy: {}", e),
+            }
+
+            sleep(RETENTION_INTERVAL).await;
+        }
+    }
+
+    /// Deletes the tasks that fall out of the retention policy, and returns how many were deleted.
+    async fn prune(&self) -> Result<usize> {
+        let mut deleted = 0;
+
+        if let Some(max_count) = self.max_count {
+            let last = self.task_store.list_tasks(None, None, Some(1)).await?;
+            let from = last.first().and_then(|task| task.id.checked_sub(max_count));
+
+            if let Some(from) = from {
+                deleted += self
+                    .task_store
+                    .delete_tasks(Some(from), None, self.update_file_store.clone())
+                    .await?;
+            }
+        }
+
+        if let Some(max_age) = self.max_age {
+            let oldest = OffsetDateTime::now_utc() - max_age;
+            let mut filter = TaskFilter::default();
+            filter.filter_fn(move |task| task.finished_at().map_or(false, |date| date < oldest));
+
+            deleted += self
+                .task_store
+                .delete_tasks(None, Some(filter), self.update_file_store.clone())
+                .await?;
+        }
+
+        Ok(deleted)
+    }
+}
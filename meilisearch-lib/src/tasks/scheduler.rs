@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque};
+use std::collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
 use std::sync::Arc;
@@ -8,21 +8,27 @@ use std::time::Duration;
 use atomic_refcell::AtomicRefCell;
 use milli::update::IndexDocumentsMethod;
 use time::OffsetDateTime;
-use tokio::sync::{watch, RwLock};
+use tokio::sync::{broadcast, watch, RwLock};
 
 use crate::options::SchedulerConfig;
 use crate::update_file_store::UpdateFileStore;
 
-use super::batch::Batch;
-use super::error::Result;
-use super::task::{Job, Task, TaskContent, TaskEvent, TaskId};
+use super::batch::{Batch, BatchId};
+use super::error::{Result, TaskError};
+use super::task::{DocumentDeletion, Job, Task, TaskContent, TaskEvent, TaskId, TaskPriority};
 use super::update_loop::UpdateLoop;
 use super::{TaskFilter, TaskPerformer, TaskStore};
 
+/// The number of task updates a subscriber can lag behind before missing some of them.
+const TASK_UPDATES_CAPACITY: usize = 1024;
+
 #[derive(Eq, Debug, Clone, Copy)]
 enum TaskType {
     DocumentAddition { number: usize },
     DocumentUpdate { number: usize },
+    DocumentMerge { number: usize },
+    DocumentDeletion,
+    SettingsUpdate,
     Other,
 }
 
@@ -33,6 +39,9 @@ impl PartialEq for TaskType {
             (self, other),
             (Self::DocumentAddition { .. }, Self::DocumentAddition { .. })
                 | (Self::DocumentUpdate { .. }, Self::DocumentUpdate { .. })
+                | (Self::DocumentMerge { .. }, Self::DocumentMerge { .. })
+                | (Self::DocumentDeletion, Self::DocumentDeletion)
+                | (Self::SettingsUpdate, Self::SettingsUpdate)
         )
     }
 }
@@ -41,6 +50,45 @@ impl PartialEq for TaskType {
 struct PendingTask {
     kind: TaskType,
     id: TaskId,
+    priority: TaskPriority,
+}
+
+impl PendingTask {
+    fn new(task: &Task) -> Self {
+        let kind = match task.content {
+            TaskContent::DocumentAddition {
+                documents_count,
+                deep_merge: true,
+                ..
+            } => TaskType::DocumentMerge {
+                number: documents_count,
+            },
+            TaskContent::DocumentAddition {
+                documents_count,
+                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                ..
+            } => TaskType::DocumentAddition {
+                number: documents_count,
+            },
+            TaskContent::DocumentAddition {
+                documents_count,
+                merge_strategy: IndexDocumentsMethod::UpdateDocuments,
+                ..
+            } => TaskType::DocumentUpdate {
+                number: documents_count,
+            },
+            // Only the deletions by ids can be merged together.
+            TaskContent::DocumentDeletion(DocumentDeletion::Ids(_)) => TaskType::DocumentDeletion,
+            TaskContent::SettingsUpdate { .. } => TaskType::SettingsUpdate,
+            _ => TaskType::Other,
+        };
+
+        Self {
+            kind,
+            id: task.id,
+            priority: task.priority,
+        }
+    }
 }
 
 impl PartialEq for PendingTask {
@@ -55,6 +103,7 @@ impl PartialOrd for PendingTask {
     }
 }
 
+/// The tasks of an index are always processed in order, regardless of their priority.
 impl Ord for PendingTask {
     fn cmp(&self, other: &Self) -> Ordering {
         self.id.cmp(&other.id).reverse()
@@ -65,6 +114,10 @@ impl Ord for PendingTask {
 struct TaskList {
     index: String,
     tasks: BinaryHeap<PendingTask>,
+    /// The round at which a batch was last scheduled from this list.
+    scheduled_at: u64,
+    /// The highest priority of the pending tasks.
+    priority: TaskPriority,
 }
 
 impl Deref for TaskList {
@@ -82,12 +135,24 @@ impl DerefMut for TaskList {
 }
 
 impl TaskList {
-    fn new(index: String) -> Self {
+    fn new(index: String, scheduled_at: u64) -> Self {
         Self {
             index,
             tasks: Default::default(),
+            scheduled_at,
+            priority: TaskPriority::Low,
         }
     }
+
+    /// Recomputes the priority of the list after tasks were removed from it.
+    fn refresh_priority(&mut self) {
+        self.priority = self
+            .tasks
+            .iter()
+            .map(|task| task.priority)
+            .max()
+            .unwrap_or(TaskPriority::Low);
+    }
 }
 
 impl PartialEq for TaskList {
@@ -98,13 +163,20 @@ impl PartialEq for TaskList {
 
 impl Eq for TaskList {}
 
+/// The list holding the highest priority task comes first. Among lists of the same priority, the
+/// list that was scheduled the longest time ago comes first, so that the indexes are scheduled in
+/// a round-robin fashion. Ties are broken by the lowest pending task id.
 impl Ord for TaskList {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self.peek(), other.peek()) {
             (None, None) => Ordering::Equal,
             (None, Some(_)) => Ordering::Less,
             (Some(_), None) => Ordering::Greater,
-            (Some(lhs), Some(rhs)) => lhs.cmp(rhs),
+            (Some(lhs), Some(rhs)) => self
+                .priority
+                .cmp(&other.priority)
+                .then_with(|| self.scheduled_at.cmp(&other.scheduled_at).reverse())
+                .then_with(|| lhs.cmp(rhs)),
         }
     }
 }
@@ -119,32 +191,21 @@ impl PartialOrd for TaskList {
 struct TaskQueue {
     /// Maps index uids to their TaskList, for quick access
     index_tasks: HashMap<String, Arc<AtomicRefCell<TaskList>>>,
-    /// A queue that orders TaskList by the priority of their fist update
+    /// A queue that orders TaskList by the priority of their tasks, the round they were last
+    /// scheduled at, and then by the id of their fist update
     queue: BinaryHeap<Arc<AtomicRefCell<TaskList>>>,
+    /// Incremented each time a TaskList is scheduled
+    round: u64,
+    /// The indexes that have a batch being processed, their tasks can't be scheduled
+    processing: HashSet<String>,
 }
 
 impl TaskQueue {
     fn insert(&mut self, task: Task) {
+        let pending = PendingTask::new(&task);
         let uid = task.index_uid.into_inner();
-        let id = task.id;
-        let kind = match task.content {
-            TaskContent::DocumentAddition {
-                documents_count,
-                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
-                ..
-            } => TaskType::DocumentAddition {
-                number: documents_count,
-            },
-            TaskContent::DocumentAddition {
-                documents_count,
-                merge_strategy: IndexDocumentsMethod::UpdateDocuments,
-                ..
-            } => TaskType::DocumentUpdate {
-                number: documents_count,
-            },
-            _ => TaskType::Other,
-        };
-        let task = PendingTask { kind, id };
+        let PendingTask { id, priority, .. } = pending;
+        let task = pending;
 
         match self.index_tasks.entry(uid) {
             Entry::Occupied(entry) => {
@@ -158,10 +219,21 @@ impl TaskQueue {
                 assert!(list.peek().map(|old_id| id >= old_id.id).unwrap_or(true));
 
                 list.push(task);
+
+                if priority > list.priority {
+                    list.priority = priority;
+                    drop(list);
+                    // The priority of the list was raised, so the queue needs to be rebuilt to
+                    // restore the heap ordering.
+                    let queue = std::mem::take(&mut self.queue);
+                    self.queue = queue.into_iter().collect();
+                }
             }
             Entry::Vacant(entry) => {
-                let mut task_list = TaskList::new(entry.key().to_owned());
+                // A new list is scheduled after the lists that are already waiting.
+                let mut task_list = TaskList::new(entry.key().to_owned(), self.round);
                 task_list.push(task);
+                task_list.priority = priority;
                 let task_list = Arc::new(AtomicRefCell::new(task_list));
                 entry.insert(task_list.clone());
                 self.queue.push(task_list);
@@ -169,13 +241,27 @@ impl TaskQueue {
         }
     }
 
-    /// Passes a context with a view to the task list of the next index to schedule. It is
-    /// guaranteed that the first id from task list will be the lowest pending task id.
+    /// Passes a context with a view to the task list of the next index to schedule. The indexes
+    /// are scheduled in turn, so that an index with many pending tasks cannot starve the others.
+    /// The indexes that are being processed are skipped.
     fn head_mut<R>(&mut self, mut f: impl FnMut(&mut TaskList) -> R) -> Option<R> {
-        let head = self.queue.pop()?;
+        let mut skipped = Vec::new();
+        let head = loop {
+            match self.queue.pop() {
+                Some(list) if self.processing.contains(&list.borrow().index) => skipped.push(list),
+                Some(list) => break Some(list),
+                None => break None,
+            }
+        };
+        self.queue.extend(skipped);
+        let head = head?;
+        self.round += 1;
         let result = {
             let mut ref_head = head.borrow_mut();
-            f(&mut *ref_head)
+            ref_head.scheduled_at = self.round;
+            let result = f(&mut *ref_head);
+            ref_head.refresh_priority();
+            result
         };
         if !head.borrow().tasks.is_empty() {
             // After being mutated, the head is reinserted to the correct position.
@@ -187,21 +273,103 @@ impl TaskQueue {
         Some(result)
     }
 
+    /// Removes the pending task `id` from the task list of `index_uid`, returning whether the task
+    /// was found in the queue.
+    fn remove(&mut self, index_uid: &str, id: TaskId) -> bool {
+        let list = match self.index_tasks.get(index_uid) {
+            Some(list) => list.clone(),
+            None => return false,
+        };
+
+        let removed = {
+            let mut list = list.borrow_mut();
+            let len = list.len();
+            let tasks = std::mem::take(&mut list.tasks);
+            list.tasks = tasks.into_iter().filter(|task| task.id != id).collect();
+            list.refresh_priority();
+            list.len() != len
+        };
+
+        if removed {
+            if list.borrow().is_empty() {
+                self.index_tasks.remove(index_uid);
+            }
+
+            // The head of the list may have changed, so the queue needs to be rebuilt to restore
+            // the heap ordering.
+            let queue = std::mem::take(&mut self.queue);
+            self.queue = queue
+                .into_iter()
+                .filter(|list| !list.borrow().is_empty())
+                .collect();
+        }
+
+        removed
+    }
+
+    /// Puts back the tasks of a batch that could not be processed. They were the first pending
+    /// tasks of their index, so they are scheduled again before the others.
+    fn requeue(&mut self, tasks: &[Task]) {
+        for task in tasks {
+            let pending = PendingTask::new(task);
+            match self.index_tasks.entry(task.index_uid.as_str().to_string()) {
+                Entry::Occupied(entry) => {
+                    let mut list = entry.get().borrow_mut();
+                    list.push(pending);
+                    list.priority = list.priority.max(pending.priority);
+                }
+                Entry::Vacant(entry) => {
+                    let mut task_list = TaskList::new(entry.key().to_owned(), self.round);
+                    task_list.push(pending);
+                    task_list.priority = pending.priority;
+                    let task_list = Arc::new(AtomicRefCell::new(task_list));
+                    entry.insert(task_list.clone());
+                    self.queue.push(task_list);
+                }
+            }
+        }
+
+        // The heads of the lists changed, so the queue needs to be rebuilt to restore the heap
+        // ordering.
+        let queue = std::mem::take(&mut self.queue);
+        self.queue = queue.into_iter().collect();
+    }
+
+    /// Marks `index_uid` as being processed, until `finish_processing` is called.
+    fn start_processing(&mut self, index_uid: String) {
+        self.processing.insert(index_uid);
+    }
+
+    fn finish_processing(&mut self, index_uid: &str) {
+        self.processing.remove(index_uid);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.queue.is_empty() && self.index_tasks.is_empty()
     }
 }
 
+/// A batch that is being processed.
+struct ProcessingBatch {
+    index_uid: String,
+    ids: Vec<TaskId>,
+}
+
 pub struct Scheduler {
     jobs: VecDeque<Job>,
     tasks: TaskQueue,
 
     store: TaskStore,
-    processing: Vec<TaskId>,
+    /// The batches being processed, they all belong to different indexes.
+    processing: HashMap<BatchId, ProcessingBatch>,
+    /// Whether a job is being processed. Jobs are processed alone.
+    processing_job: bool,
     next_fetched_task_id: TaskId,
     config: SchedulerConfig,
     /// Notifies the update loop that a new task was received
     notifier: watch::Sender<()>,
+    /// Broadcasts the tasks each time a new event is pushed to them
+    task_updates: broadcast::Sender<Task>,
 }
 
 impl Scheduler {
@@ -214,8 +382,10 @@ impl Scheduler {
         P: TaskPerformer,
     {
         let (notifier, rcv) = watch::channel(());
+        let (task_updates, _) = broadcast::channel(TASK_UPDATES_CAPACITY);
 
         let debounce_time = config.debounce_duration_sec;
+        let batch_workers = config.batch_workers.unwrap_or(1).max(1);
 
         // Disable autobatching
         if !config.enable_auto_batching {
@@ -227,10 +397,12 @@ impl Scheduler {
             tasks: TaskQueue::default(),
 
             store,
-            processing: Vec::new(),
+            processing: HashMap::new(),
+            processing_job: false,
             next_fetched_task_id: 0,
             config,
             notifier,
+            task_updates,
         };
 
         // Notify update loop to start processing pending updates immediately after startup.
@@ -238,14 +410,17 @@ impl Scheduler {
 
         let this = Arc::new(RwLock::new(this));
 
-        let update_loop = UpdateLoop::new(
-            this.clone(),
-            performer,
-            debounce_time.filter(|&v| v > 0).map(Duration::from_secs),
-            rcv,
-        );
+        // Each update loop processes one batch at a time.
+        for _ in 0..batch_workers {
+            let update_loop = UpdateLoop::new(
+                this.clone(),
+                performer.clone(),
+                debounce_time.filter(|&v| v > 0).map(Duration::from_secs),
+                rcv.clone(),
+            );
 
-        tokio::task::spawn_local(update_loop.run());
+            tokio::task::spawn_local(update_loop.run());
+        }
 
         Ok(this)
     }
@@ -259,9 +434,33 @@ impl Scheduler {
         self.tasks.insert(task);
     }
 
-    /// Clears the processing list, this method should be called when the processing of a batch is finished.
-    pub fn finish(&mut self) {
-        self.processing.clear();
+    /// Removes the batch `id` from the processing batches, this method should be called when the
+    /// processing of a batch is finished. Its index can then be scheduled again.
+    pub fn finish(&mut self, id: BatchId) {
+        if let Some(batch) = self.processing.remove(&id) {
+            self.tasks.finish_processing(&batch.index_uid);
+        }
+        self.notify_if_not_empty();
+    }
+
+    /// Puts the tasks of the batch `id` back in the queue, and removes the batch from the processing
+    /// batches. This method should be called when a batch is given up before being processed, so
+    /// that its tasks are scheduled again.
+    pub fn requeue(&mut self, id: BatchId, tasks: &[Task]) {
+        self.tasks.requeue(tasks);
+        self.finish(id);
+    }
+
+    /// This method should be called when the processing of a job is finished.
+    pub fn finish_job(&mut self) {
+        self.processing_job = false;
+        self.notify_if_not_empty();
+    }
+
+    fn is_processing(&self, id: TaskId) -> bool {
+        self.processing
+            .values()
+            .any(|batch| batch.ids.contains(&id))
     }
 
     pub fn notify(&self) {
@@ -274,8 +473,23 @@ impl Scheduler {
         }
     }
 
+    /// Returns a receiver of the tasks, sent each time a new event is pushed to one of them.
+    pub fn subscribe(&self) -> broadcast::Receiver<Task> {
+        self.task_updates.subscribe()
+    }
+
+    /// Sends `task` to the subscribers of the task updates.
+    pub fn publish(&self, task: &Task) {
+        // Only clone the task if someone is listening.
+        if self.task_updates.receiver_count() > 0 {
+            let _ = self.task_updates.send(task.clone());
+        }
+    }
+
     pub async fn update_tasks(&self, tasks: Vec<Task>) -> Result<Vec<Task>> {
-        self.store.update_tasks(tasks).await
+        let tasks = self.store.update_tasks(tasks).await?;
+        tasks.iter().for_each(|task| self.publish(task));
+        Ok(tasks)
     }
 
     pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
@@ -284,17 +498,35 @@ impl Scheduler {
 
     pub async fn list_tasks(
         &self,
-        offset: Option<TaskId>,
+        from: Option<TaskId>,
         filter: Option<TaskFilter>,
         limit: Option<usize>,
     ) -> Result<Vec<Task>> {
-        self.store.list_tasks(offset, filter, limit).await
+        self.store.list_tasks(from, filter, limit).await
+    }
+
+    /// Cancels an enqueued task: a `Canceled` event is pushed to the task in the store, and the task
+    /// is removed from the pending queue. Tasks that are processing or finished can't be canceled.
+    pub async fn cancel_task(&mut self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
+        let mut task = self.store.get_task(id, filter).await?;
+
+        if task.is_finished() || self.is_processing(id) {
+            return Err(TaskError::TaskNotCancelable(id));
+        }
+
+        task.events
+            .push(TaskEvent::Canceled(OffsetDateTime::now_utc()));
+        self.store.update_tasks(vec![task.clone()]).await?;
+        self.tasks.remove(&task.index_uid, id);
+        self.publish(&task);
+
+        Ok(task)
     }
 
     pub async fn get_processing_tasks(&self) -> Result<Vec<Task>> {
         let mut tasks = Vec::new();
 
-        for id in self.processing.iter() {
+        for id in self.processing.values().flat_map(|batch| batch.ids.iter()) {
             let task = self.store.get_task(*id, None).await?;
             tasks.push(task);
         }
@@ -311,15 +543,10 @@ impl Scheduler {
         // We must NEVER re-enqueue an already processed task! It's content uuid would point to an unexisting file.
         //
         // TODO(marin): This may create some latency when the first batch lazy loads the pending updates.
-        let mut filter = TaskFilter::default();
-        filter.filter_fn(|task| !task.is_finished());
-
         self.store
-            .list_tasks(Some(self.next_fetched_task_id), Some(filter), None)
+            .fetch_unfinished_tasks(Some(self.next_fetched_task_id))
             .await?
             .into_iter()
-            // The tasks arrive in reverse order, and we need to insert them in order.
-            .rev()
             .for_each(|t| {
                 self.next_fetched_task_id = t.id + 1;
                 self.register_task(t);
@@ -328,29 +555,37 @@ impl Scheduler {
         Ok(())
     }
 
-    /// Prepare the next batch, and set `processing` to the ids in that batch.
+    /// Prepare the next batch, and add it to the `processing` batches.
     pub async fn prepare(&mut self) -> Result<Pending> {
-        // If there is a job to process, do it first.
-        if let Some(job) = self.jobs.pop_front() {
-            // There is more work to do, notify the update loop
-            self.notify_if_not_empty();
+        // Jobs are processed alone: no batch is scheduled until the pending jobs are processed.
+        if self.processing_job {
+            return Ok(Pending::Nothing);
+        }
+
+        // If there is a job to process, do it first, as soon as the processing batches are finished.
+        if !self.jobs.is_empty() {
+            if !self.processing.is_empty() {
+                return Ok(Pending::Nothing);
+            }
+
+            let job = self.jobs.pop_front().unwrap();
+            self.processing_job = true;
             return Ok(Pending::Job(job));
         }
         // Try to fill the queue with pending tasks.
         self.fetch_pending_tasks().await?;
 
-        make_batch(&mut self.tasks, &mut self.processing, &self.config);
-
-        log::debug!("prepared batch with {} tasks", self.processing.len());
+        let mut ids = Vec::new();
+        make_batch(&mut self.tasks, &mut ids, &self.config);
 
-        if !self.processing.is_empty() {
-            let ids = std::mem::take(&mut self.processing);
+        log::debug!("prepared batch with {} tasks", ids.len());
 
+        if !ids.is_empty() {
             let (ids, mut tasks) = self.store.get_pending_tasks(ids).await?;
 
             // The batch id is the id of the first update it contains
-            let id = match tasks.first() {
-                Some(Task { id, .. }) => *id,
+            let (id, index_uid) = match tasks.first() {
+                Some(Task { id, index_uid, .. }) => (*id, index_uid.as_str().to_owned()),
                 _ => panic!("invalid batch"),
             };
 
@@ -361,7 +596,9 @@ impl Scheduler {
                 })
             });
 
-            self.processing = ids;
+            self.tasks.start_processing(index_uid.clone());
+            self.processing
+                .insert(id, ProcessingBatch { index_uid, ids });
 
             let batch = Batch {
                 id,
@@ -394,6 +631,7 @@ fn make_batch(tasks: &mut TaskQueue, processing: &mut Vec<TaskId>, config: &Sche
         Some(PendingTask {
             kind: TaskType::Other,
             id,
+            ..
         }) => {
             processing.push(id);
             list.pop();
@@ -414,6 +652,7 @@ fn make_batch(tasks: &mut TaskQueue, processing: &mut Vec<TaskId>, config: &Sche
                     // We check that bound only after adding the current task to the batch, so that a batch contains at least one task.
                     match pending.kind {
                         TaskType::DocumentUpdate { number }
+                        | TaskType::DocumentMerge { number }
                         | TaskType::DocumentAddition { number } => {
                             doc_count += number;
 
@@ -433,10 +672,17 @@ fn make_batch(tasks: &mut TaskQueue, processing: &mut Vec<TaskId>, config: &Sche
 
 #[cfg(test)]
 mod test {
+    use std::sync::atomic::{self, AtomicUsize};
+
     use milli::update::IndexDocumentsMethod;
+    use nelson::Mocker;
+    use tokio::time::sleep;
     use uuid::Uuid;
 
-    use crate::{index_resolver::IndexUid, tasks::task::TaskContent};
+    use crate::index::Settings;
+    use crate::index_resolver::IndexUid;
+    use crate::tasks::task::{DocumentDeletion, TaskContent};
+    use crate::tasks::MockTaskPerformer;
 
     use super::*;
 
@@ -446,6 +692,7 @@ mod test {
             index_uid: IndexUid::new_unchecked(index_uid),
             content,
             events: vec![],
+            priority: Default::default(),
         }
     }
 
@@ -476,6 +723,79 @@ mod test {
         assert!(queue.queue.is_empty());
     }
 
+    #[test]
+    fn remove_pending_tasks() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, "test1", TaskContent::IndexDeletion));
+        queue.insert(gen_task(1, "test2", TaskContent::IndexDeletion));
+        queue.insert(gen_task(2, "test2", TaskContent::IndexDeletion));
+        queue.insert(gen_task(3, "test1", TaskContent::IndexDeletion));
+
+        assert!(queue.remove("test1", 0));
+        assert!(!queue.remove("test1", 0));
+        assert!(!queue.remove("test3", 1));
+
+        // test2 now holds the lowest pending task id and must be scheduled first.
+        let test2_tasks = queue
+            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(test2_tasks, &[1, 2]);
+
+        assert!(queue.remove("test1", 3));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn schedule_high_priority_index_first() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, "import", TaskContent::IndexDeletion));
+        queue.insert(gen_task(1, "import", TaskContent::IndexDeletion));
+        queue.insert(gen_task(2, "small", TaskContent::IndexDeletion));
+        let mut task = gen_task(3, "small", TaskContent::IndexDeletion);
+        task.priority = TaskPriority::High;
+        queue.insert(task);
+        let mut task = gen_task(4, "import", TaskContent::IndexDeletion);
+        task.priority = TaskPriority::Low;
+        queue.insert(task);
+
+        let mut batch = Vec::new();
+        let mut scheduled = Vec::new();
+        let config = SchedulerConfig::default();
+
+        while !queue.is_empty() {
+            batch.clear();
+            make_batch(&mut queue, &mut batch, &config);
+            scheduled.extend_from_slice(&batch);
+        }
+
+        // The tasks of the `small` index are processed in order until the high priority task is
+        // processed, then the indexes are scheduled in turn again.
+        assert_eq!(scheduled, &[2, 3, 0, 1, 4]);
+    }
+
+    #[test]
+    fn skip_processing_indexes() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, "test1", TaskContent::IndexDeletion));
+        queue.insert(gen_task(1, "test2", TaskContent::IndexDeletion));
+        queue.insert(gen_task(2, "test1", TaskContent::IndexDeletion));
+
+        let mut batch = Vec::new();
+        let config = SchedulerConfig::default();
+
+        queue.start_processing("test1".to_string());
+        make_batch(&mut queue, &mut batch, &config);
+        assert_eq!(batch, &[1]);
+
+        // test1 is still processing, nothing can be scheduled.
+        make_batch(&mut queue, &mut batch, &config);
+        assert!(batch.is_empty());
+
+        queue.finish_processing("test1");
+        make_batch(&mut queue, &mut batch, &config);
+        assert_eq!(batch, &[0]);
+    }
+
     #[test]
     fn test_make_batch() {
         let mut queue = TaskQueue::default();
@@ -485,6 +805,7 @@ mod test {
             primary_key: Some("test".to_string()),
             documents_count: 0,
             allow_index_creation: true,
+            deep_merge: false,
         };
         queue.insert(gen_task(0, "test1", content.clone()));
         queue.insert(gen_task(1, "test2", content.clone()));
@@ -501,26 +822,183 @@ mod test {
         make_batch(&mut queue, &mut batch, &config);
         assert_eq!(batch, &[0, 4]);
 
+        // The indexes are scheduled in turn, so the batches of test1 and test2 alternate.
         batch.clear();
         make_batch(&mut queue, &mut batch, &config);
         assert_eq!(batch, &[1]);
 
+        batch.clear();
+        make_batch(&mut queue, &mut batch, &config);
+        assert_eq!(batch, &[5]);
+
         batch.clear();
         make_batch(&mut queue, &mut batch, &config);
         assert_eq!(batch, &[2]);
 
+        batch.clear();
+        make_batch(&mut queue, &mut batch, &config);
+        assert_eq!(batch, &[7]);
+
         batch.clear();
         make_batch(&mut queue, &mut batch, &config);
         assert_eq!(batch, &[3, 6]);
 
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn make_batch_settings_and_deletions() {
+        let mut queue = TaskQueue::default();
+        let settings = TaskContent::SettingsUpdate {
+            settings: Settings::default(),
+            is_deletion: false,
+            allow_index_creation: true,
+        };
+        let deletion = TaskContent::DocumentDeletion(DocumentDeletion::Ids(vec!["1".to_string()]));
+        queue.insert(gen_task(0, "test1", settings.clone()));
+        queue.insert(gen_task(1, "test1", settings.clone()));
+        queue.insert(gen_task(2, "test1", deletion.clone()));
+        queue.insert(gen_task(
+            3,
+            "test1",
+            TaskContent::DocumentDeletion(DocumentDeletion::Clear),
+        ));
+        queue.insert(gen_task(4, "test1", settings));
+        queue.insert(gen_task(5, "test1", deletion));
+
+        let mut batch = Vec::new();
+
+        let config = SchedulerConfig::default();
+        make_batch(&mut queue, &mut batch, &config);
+        assert_eq!(batch, &[0, 1]);
+
         batch.clear();
         make_batch(&mut queue, &mut batch, &config);
-        assert_eq!(batch, &[5]);
+        assert_eq!(batch, &[2]);
 
+        // Clearing the documents is never batched.
         batch.clear();
         make_batch(&mut queue, &mut batch, &config);
-        assert_eq!(batch, &[7]);
+        assert_eq!(batch, &[3]);
+
+        batch.clear();
+        make_batch(&mut queue, &mut batch, &config);
+        assert_eq!(batch, &[4]);
+
+        batch.clear();
+        make_batch(&mut queue, &mut batch, &config);
+        assert_eq!(batch, &[5]);
 
         assert!(queue.is_empty());
     }
+
+    #[test]
+    fn busy_index_does_not_starve_others() {
+        let mut queue = TaskQueue::default();
+        for id in 0..10 {
+            queue.insert(gen_task(id, "busy", TaskContent::IndexDeletion));
+        }
+        queue.insert(gen_task(10, "other1", TaskContent::IndexDeletion));
+        queue.insert(gen_task(11, "other2", TaskContent::IndexDeletion));
+
+        let mut batch = Vec::new();
+        let mut scheduled = Vec::new();
+        let config = SchedulerConfig::default();
+
+        for _ in 0..4 {
+            batch.clear();
+            make_batch(&mut queue, &mut batch, &config);
+            scheduled.extend_from_slice(&batch);
+        }
+
+        // other1 and other2 are scheduled right after the first task of the busy index, instead
+        // of waiting for all its tasks to be processed.
+        assert_eq!(scheduled, &[0, 10, 11, 1]);
+    }
+
+    #[actix_rt::test]
+    async fn failed_task_update_requeues_batch() {
+        let mocker = Mocker::default();
+        let task = gen_task(0, "test", TaskContent::IndexDeletion);
+
+        let pending = vec![task.clone()];
+        mocker
+            .when::<Option<TaskId>, Result<Vec<Task>>>("fetch_unfinished_tasks")
+            .then(move |from| {
+                let from = from.unwrap_or_default();
+                Ok(pending.iter().filter(|t| t.id >= from).cloned().collect())
+            });
+        mocker
+            .when::<Vec<TaskId>, Result<(Vec<TaskId>, Vec<Task>)>>("get_pending_task")
+            .then(move |ids| Ok((ids, vec![task.clone()])));
+
+        // The tasks of the batch can't be marked as processing the first time only.
+        let updates = Arc::new(AtomicUsize::new(0));
+        let update_count = updates.clone();
+        mocker
+            .when::<Vec<Task>, Result<Vec<Task>>>("update_tasks")
+            .then(move |tasks| {
+                if update_count.fetch_add(1, atomic::Ordering::Relaxed) == 0 {
+                    Err(std::io::Error::new(std::io::ErrorKind::Other, "store failure").into())
+                } else {
+                    Ok(tasks)
+                }
+            });
+
+        let store = TaskStore::mock(mocker);
+        let processed = Arc::new(AtomicUsize::new(0));
+        let process_count = processed.clone();
+        let mut performer = MockTaskPerformer::new();
+        performer.expect_process_batch().returning(move |batch| {
+            process_count.fetch_add(1, atomic::Ordering::Relaxed);
+            batch
+        });
+        performer.expect_finish().returning(|_| ());
+        let scheduler =
+            Scheduler::new(store, Arc::new(performer), SchedulerConfig::default()).unwrap();
+
+        let mut finished = false;
+        for _ in 0..100 {
+            sleep(Duration::from_millis(10)).await;
+            let scheduler = scheduler.read().await;
+            if processed.load(atomic::Ordering::Relaxed) == 1
+                && scheduler.processing.is_empty()
+                && scheduler.tasks.is_empty()
+            {
+                finished = true;
+                break;
+            }
+        }
+
+        // The task was put back in the queue and processed by the next batch.
+        assert!(finished, "the task was never processed");
+        assert_eq!(processed.load(atomic::Ordering::Relaxed), 1);
+        assert_eq!(updates.load(atomic::Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn requeued_tasks_are_scheduled_first() {
+        let mut queue = TaskQueue::default();
+        let tasks: Vec<_> = (0..3)
+            .map(|id| gen_task(id, "test", TaskContent::IndexDeletion))
+            .collect();
+        for task in tasks.iter().cloned() {
+            queue.insert(task);
+        }
+
+        let mut batch = Vec::new();
+        let config = SchedulerConfig::default();
+        make_batch(&mut queue, &mut batch, &config);
+        assert_eq!(batch, &[0]);
+
+        queue.requeue(&tasks[..1]);
+        queue.insert(gen_task(3, "other", TaskContent::IndexDeletion));
+
+        let mut scheduled = Vec::new();
+        while !queue.is_empty() {
+            make_batch(&mut queue, &mut batch, &config);
+            scheduled.extend_from_slice(&batch);
+        }
+        assert_eq!(scheduled, &[0, 3, 1, 2]);
+    }
 }
@@ -3,13 +3,14 @@ use std::path::PathBuf;
 use meilisearch_error::ResponseError;
 use milli::update::{DocumentAdditionResult, IndexDocumentsMethod};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use time::OffsetDateTime;
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
 use super::batch::BatchId;
 use crate::{
-    index::{Settings, Unchecked},
+    index::{DocumentEdit, Settings, Unchecked},
     index_resolver::{error::IndexResolverError, IndexUid},
     snapshot::SnapshotJob,
 };
@@ -22,6 +23,7 @@ pub enum TaskResult {
     DocumentAddition { indexed_documents: u64 },
     DocumentDeletion { deleted_documents: u64 },
     ClearAll { deleted_documents: u64 },
+    DocumentEdition { edited_documents: u64 },
     Other,
 }
 
@@ -64,6 +66,28 @@ pub enum TaskEvent {
         #[serde(with = "time::serde::rfc3339")]
         timestamp: OffsetDateTime,
     },
+    Canceled(
+        #[cfg_attr(test, proptest(strategy = "test::datetime_strategy()"))]
+        #[serde(with = "time::serde::rfc3339")]
+        OffsetDateTime,
+    ),
+}
+
+/// The priority of a task. The scheduler processes first the indexes with the highest priority
+/// pending tasks, but the tasks of an index are always processed in order.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub enum TaskPriority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Default for TaskPriority {
+    fn default() -> Self {
+        Self::Normal
+    }
 }
 
 /// A task represents an operation that Meilisearch must do.
@@ -77,17 +101,49 @@ pub struct Task {
     pub index_uid: IndexUid,
     pub content: TaskContent,
     pub events: Vec<TaskEvent>,
+    #[serde(default)]
+    pub priority: TaskPriority,
 }
 
 impl Task {
     /// Return true when a task is finished.
-    /// A task is finished when its last state is either `Succeeded` or `Failed`.
+    /// A task is finished when its last state is either `Succeeded`, `Failed` or `Canceled`.
     pub fn is_finished(&self) -> bool {
         self.events.last().map_or(false, |event| {
-            matches!(event, TaskEvent::Succeded { .. } | TaskEvent::Failed { .. })
+            matches!(
+                event,
+                TaskEvent::Succeded { .. } | TaskEvent::Failed { .. } | TaskEvent::Canceled(_)
+            )
         })
     }
 
+    /// Return true when the processing of the task started but never finished, which happens when
+    /// the instance stopped in the middle of a batch.
+    pub fn is_interrupted(&self) -> bool {
+        matches!(
+            self.events.last(),
+            Some(TaskEvent::Batched { .. }) | Some(TaskEvent::Processing(_))
+        )
+    }
+
+    /// Return the date at which the task was enqueued, if it is known.
+    pub fn enqueued_at(&self) -> Option<OffsetDateTime> {
+        match self.events.first() {
+            Some(TaskEvent::Created(ts)) => Some(*ts),
+            _ => None,
+        }
+    }
+
+    /// Return the date at which the task was finished, if it is finished.
+    pub fn finished_at(&self) -> Option<OffsetDateTime> {
+        match self.events.last() {
+            Some(TaskEvent::Succeded { timestamp, .. })
+            | Some(TaskEvent::Failed { timestamp, .. })
+            | Some(TaskEvent::Canceled(timestamp)) => Some(*timestamp),
+            _ => None,
+        }
+    }
+
     /// Return the content_uuid of the `Task` if there is one.
     pub fn get_content_uuid(&self) -> Option<Uuid> {
         match self {
@@ -132,6 +188,7 @@ impl Job {
 pub enum DocumentDeletion {
     Clear,
     Ids(Vec<String>),
+    Filter(#[cfg_attr(test, proptest(value = "serde_json::json!(\"id = 1\")"))] Value),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -146,8 +203,18 @@ pub enum TaskContent {
         primary_key: Option<String>,
         documents_count: usize,
         allow_index_creation: bool,
+        /// Whether the documents are deep merged into the existing ones, as a JSON merge patch.
+        #[serde(default)]
+        deep_merge: bool,
     },
     DocumentDeletion(DocumentDeletion),
+    /// Applies the edits in place to the documents matching the filter.
+    DocumentEdition {
+        #[cfg_attr(test, proptest(value = "None"))]
+        filter: Option<Value>,
+        #[cfg_attr(test, proptest(value = "Vec::new()"))]
+        edits: Vec<DocumentEdit>,
+    },
     SettingsUpdate {
         settings: Settings<Unchecked>,
         /// Indicates whether the task was a deletion
@@ -5,17 +5,21 @@ use std::io::{BufWriter, Write};
 use std::path::Path;
 use std::sync::Arc;
 
-use log::debug;
+use log::{debug, error, warn};
 use milli::heed::{Env, RwTxn};
 use time::OffsetDateTime;
 
 use super::error::TaskError;
-use super::task::{Task, TaskContent, TaskId};
+use super::task::{Task, TaskContent, TaskId, TaskPriority};
 use super::Result;
 use crate::index_resolver::IndexUid;
 use crate::tasks::task::TaskEvent;
+use crate::tasks::TaskWebhook;
 use crate::update_file_store::UpdateFileStore;
 
+/// The maximum number of task ids walked in a single write transaction when deleting tasks.
+const DELETE_TASKS_CHUNK_SIZE: usize = 1000;
+
 #[cfg(test)]
 pub use store::test::MockStore as Store;
 #[cfg(not(test))]
@@ -36,6 +40,11 @@ impl TaskFilter {
             .unwrap_or(true)
     }
 
+    /// Returns whether `task` belongs to the indexes of the filter and satisfies its predicate.
+    fn matches(&self, task: &Task) -> bool {
+        self.pass(task) && self.filter_fn.as_ref().map_or(true, |f| f(task))
+    }
+
     /// Adds an index to the filter, so the filter must match this index.
     pub fn filter_index(&mut self, index: String) {
         self.indexes
@@ -43,6 +52,8 @@ impl TaskFilter {
             .insert(index);
     }
 
+    /// Adds a custom predicate that the tasks must satisfy. Only one predicate can be set, setting
+    /// a new one replaces the previous one.
     pub fn filter_fn(&mut self, f: impl Fn(&Task) -> bool + Sync + Send + 'static) {
         self.filter_fn.replace(Box::new(f));
     }
@@ -50,12 +61,15 @@ impl TaskFilter {
 
 pub struct TaskStore {
     store: Arc<Store>,
+    /// Notified of the tasks reaching a terminal state, whatever the reason.
+    webhook: Option<TaskWebhook>,
 }
 
 impl Clone for TaskStore {
     fn clone(&self) -> Self {
         Self {
             store: self.store.clone(),
+            webhook: self.webhook.clone(),
         }
     }
 }
@@ -63,10 +77,34 @@ impl Clone for TaskStore {
 impl TaskStore {
     pub fn new(env: Arc<milli::heed::Env>) -> Result<Self> {
         let store = Arc::new(Store::new(env)?);
-        Ok(Self { store })
+        Ok(Self {
+            store,
+            webhook: None,
+        })
+    }
+
+    /// Sets the webhook notified of the finished tasks. Every terminal event is written through the
+    /// task store, so the tasks that succeeded, failed, were canceled or interrupted by a crash are
+    /// all notified.
+    pub fn set_webhook(&mut self, webhook: TaskWebhook) {
+        self.webhook.replace(webhook);
+    }
+
+    fn notify_finished<'a>(&self, tasks: impl IntoIterator<Item = &'a Task>) {
+        if let Some(ref webhook) = self.webhook {
+            tasks
+                .into_iter()
+                .filter(|task| task.is_finished())
+                .for_each(|task| webhook.notify(task.clone()));
+        }
     }
 
-    pub async fn register(&self, index_uid: IndexUid, content: TaskContent) -> Result<Task> {
+    pub async fn register(
+        &self,
+        index_uid: IndexUid,
+        content: TaskContent,
+        priority: TaskPriority,
+    ) -> Result<Task> {
         debug!("registering update: {:?}", content);
         let store = self.store.clone();
         let task = tokio::task::spawn_blocking(move || -> Result<Task> {
@@ -78,6 +116,7 @@ impl TaskStore {
                 index_uid,
                 content,
                 events: vec![created_at],
+                priority,
             };
 
             store.put(&mut txn, &task)?;
@@ -95,6 +134,58 @@ impl TaskStore {
         Ok(())
     }
 
+    /// Restores the tasks that were interrupted by a crash to a consistent state. The tasks that
+    /// can safely be processed again are re-enqueued, and the others are marked as failed. A task
+    /// whose update file is gone can't be processed again either.
+    ///
+    /// This must be called before the scheduler starts. Returns the recovered tasks.
+    pub fn recover_interrupted_tasks(
+        &self,
+        update_file_store: &UpdateFileStore,
+    ) -> Result<Vec<Task>> {
+        let mut txn = self.store.wtxn()?;
+        let mut tasks = self.store.fetch_unfinished_tasks(&txn, None)?;
+        tasks.retain(Task::is_interrupted);
+
+        for task in tasks.iter_mut() {
+            let has_update_file = task
+                .get_content_uuid()
+                .map_or(true, |uuid| update_file_store.exists(uuid));
+
+            if can_be_processed_again(task) && has_update_file {
+                // Drops the events pushed since the task was batched, so that it is enqueued again.
+                if let Some(batched) = task.events.iter().position(|event| {
+                    matches!(event, TaskEvent::Batched { .. } | TaskEvent::Processing(_))
+                }) {
+                    task.events.truncate(batched);
+                }
+            } else {
+                task.events.push(TaskEvent::Failed {
+                    error: TaskError::Interrupted(task.id).into(),
+                    timestamp: OffsetDateTime::now_utc(),
+                });
+            }
+
+            self.store.put(&mut txn, task)?;
+        }
+
+        txn.commit()?;
+
+        self.notify_finished(&tasks);
+
+        if !tasks.is_empty() {
+            let failed = tasks.iter().filter(|task| task.is_finished()).count();
+            warn!(
+                "Recovered {} interrupted tasks: {} were enqueued again and {} were marked as failed.",
+                tasks.len(),
+                tasks.len() - failed,
+                failed
+            );
+        }
+
+        Ok(tasks)
+    }
+
     pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
         let store = self.store.clone();
         let task = tokio::task::spawn_blocking(move || -> Result<_> {
@@ -149,12 +240,25 @@ impl TaskStore {
         })
         .await??;
 
+        self.notify_finished(&tasks);
+
         Ok(tasks)
     }
 
+    pub async fn fetch_unfinished_tasks(&self, from: Option<TaskId>) -> Result<Vec<Task>> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            let tasks = store.fetch_unfinished_tasks(&txn, from)?;
+            Ok(tasks)
+        })
+        .await?
+    }
+
     pub async fn list_tasks(
         &self,
-        offset: Option<TaskId>,
+        from: Option<TaskId>,
         filter: Option<TaskFilter>,
         limit: Option<usize>,
     ) -> Result<Vec<Task>> {
@@ -162,12 +266,89 @@ impl TaskStore {
 
         tokio::task::spawn_blocking(move || {
             let txn = store.rtxn()?;
-            let tasks = store.list_tasks(&txn, offset, filter, limit)?;
+            let tasks = store.list_tasks(&txn, from, filter, limit)?;
             Ok(tasks)
         })
         .await?
     }
 
+    /// Deletes the finished tasks matching `filter`, starting from the task id `from` and going
+    /// down, along with their update files. Returns how many tasks were deleted.
+    ///
+    /// The most recent task is never deleted, since the id of the next task is derived from it.
+    pub async fn delete_tasks(
+        &self,
+        from: Option<TaskId>,
+        filter: Option<TaskFilter>,
+        update_file_store: UpdateFileStore,
+    ) -> Result<usize> {
+        self.delete_tasks_by_chunks(from, filter, update_file_store, DELETE_TASKS_CHUNK_SIZE)
+            .await
+    }
+
+    /// Walks the task ids by chunks of `chunk_size`, and deletes the matching tasks of each chunk
+    /// in its own write transaction, so that the other writers are never blocked for long.
+    async fn delete_tasks_by_chunks(
+        &self,
+        mut from: Option<TaskId>,
+        filter: Option<TaskFilter>,
+        update_file_store: UpdateFileStore,
+        chunk_size: usize,
+    ) -> Result<usize> {
+        let filter = filter.map(Arc::new);
+        let mut deleted = 0;
+
+        loop {
+            let store = self.store.clone();
+            let filter = filter.clone();
+            let (content_uuids, next) = tokio::task::spawn_blocking(move || -> Result<_> {
+                let mut txn = store.wtxn()?;
+                let next_task_id = store.next_task_id(&mut txn)?;
+
+                // One more id is fetched to know where the next chunk starts.
+                let mut ids = store.task_ids(&txn, from, filter.as_deref(), chunk_size + 1)?;
+                let next = if ids.len() > chunk_size {
+                    ids.pop()
+                } else {
+                    None
+                };
+
+                let mut content_uuids = Vec::new();
+                for id in ids {
+                    let task = match store.get(&txn, id)? {
+                        Some(task) => task,
+                        None => continue,
+                    };
+
+                    if task.is_finished()
+                        && task.id + 1 != next_task_id
+                        && filter.as_ref().map_or(true, |f| f.matches(&task))
+                    {
+                        store.delete(&mut txn, &task)?;
+                        content_uuids.push(task.get_content_uuid());
+                    }
+                }
+
+                txn.commit()?;
+
+                Ok((content_uuids, next))
+            })
+            .await??;
+
+            deleted += content_uuids.len();
+            for content_uuid in content_uuids.into_iter().flatten() {
+                if let Err(e) = update_file_store.delete_if_exists(content_uuid).await {
+                    error!("Error deleting update file {}: {}", content_uuid, e);
+                }
+            }
+
+            match next {
+                Some(next) => from = Some(next),
+                None => break Ok(deleted),
+            }
+        }
+    }
+
     pub async fn dump(
         &self,
         dir_path: impl AsRef<Path>,
@@ -221,9 +402,26 @@ impl TaskStore {
     }
 }
 
+/// Returns whether `task` can be processed again after its processing was interrupted. The index
+/// creations, updates and deletions are not processed again, since they may have been applied
+/// before the interruption.
+fn can_be_processed_again(task: &Task) -> bool {
+    matches!(
+        task.content,
+        TaskContent::DocumentAddition { .. }
+            | TaskContent::DocumentDeletion(_)
+            | TaskContent::SettingsUpdate { .. }
+    )
+}
+
 #[cfg(test)]
 pub mod test {
+    use crate::index::DocumentEdit;
+    use crate::tasks::task::{DocumentDeletion, TaskResult};
     use crate::tasks::task_store::store::test::tmp_env;
+    use meilisearch_error::ResponseError;
+    use milli::update::IndexDocumentsMethod;
+    use uuid::Uuid;
 
     use super::*;
 
@@ -256,6 +454,13 @@ pub mod test {
             Self::Mock(Arc::new(mocker))
         }
 
+        pub fn set_webhook(&mut self, webhook: TaskWebhook) {
+            match self {
+                Self::Real(s) => s.set_webhook(webhook),
+                Self::Mock(_m) => todo!(),
+            }
+        }
+
         pub async fn update_tasks(&self, tasks: Vec<Task>) -> Result<Vec<Task>> {
             match self {
                 Self::Real(s) => s.update_tasks(tasks).await,
@@ -282,6 +487,13 @@ pub mod test {
             }
         }
 
+        pub async fn fetch_unfinished_tasks(&self, from: Option<TaskId>) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.fetch_unfinished_tasks(from).await,
+                Self::Mock(m) => unsafe { m.get("fetch_unfinished_tasks").call(from) },
+            }
+        }
+
         pub async fn list_tasks(
             &self,
             from: Option<TaskId>,
@@ -294,6 +506,31 @@ pub mod test {
             }
         }
 
+        pub fn recover_interrupted_tasks(
+            &self,
+            update_file_store: &UpdateFileStore,
+        ) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.recover_interrupted_tasks(update_file_store),
+                Self::Mock(m) => unsafe { m.get("recover_interrupted_tasks").call(()) },
+            }
+        }
+
+        pub async fn delete_tasks(
+            &self,
+            from: Option<TaskId>,
+            filter: Option<TaskFilter>,
+            update_file_store: UpdateFileStore,
+        ) -> Result<usize> {
+            match self {
+                Self::Real(s) => s.delete_tasks(from, filter, update_file_store).await,
+                Self::Mock(m) => unsafe {
+                    m.get("delete_tasks")
+                        .call((from, filter, update_file_store))
+                },
+            }
+        }
+
         pub async fn dump(
             &self,
             path: impl AsRef<Path>,
@@ -305,9 +542,14 @@ pub mod test {
             }
         }
 
-        pub async fn register(&self, index_uid: IndexUid, content: TaskContent) -> Result<Task> {
+        pub async fn register(
+            &self,
+            index_uid: IndexUid,
+            content: TaskContent,
+            priority: TaskPriority,
+        ) -> Result<Task> {
             match self {
-                Self::Real(s) => s.register(index_uid, content).await,
+                Self::Real(s) => s.register(index_uid, content, priority).await,
                 Self::Mock(_m) => todo!(),
             }
         }
@@ -338,6 +580,7 @@ pub mod test {
             index_uid: IndexUid::new_unchecked("test"),
             content: TaskContent::IndexCreation { primary_key: None },
             events: Vec::new(),
+            priority: Default::default(),
         };
 
         let mut runner = TestRunner::new(Config::default());
@@ -364,4 +607,257 @@ pub mod test {
             })
             .unwrap();
     }
+
+    #[actix_rt::test]
+    async fn test_delete_finished_tasks() {
+        let tmp = tmp_env();
+        let update_dir = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(update_dir.path()).unwrap();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let mut tasks = Vec::new();
+        for _ in 0..4 {
+            let task = store
+                .register(
+                    IndexUid::new_unchecked("test"),
+                    TaskContent::IndexCreation { primary_key: None },
+                    TaskPriority::default(),
+                )
+                .await
+                .unwrap();
+            tasks.push(task);
+        }
+
+        // finish every task but the second one
+        for task in tasks.iter_mut().filter(|task| task.id != 1) {
+            task.events.push(TaskEvent::Succeded {
+                result: TaskResult::Other,
+                timestamp: OffsetDateTime::now_utc(),
+            });
+        }
+        store.update_tasks(tasks).await.unwrap();
+
+        let deleted = store
+            .delete_tasks(None, None, update_file_store)
+            .await
+            .unwrap();
+
+        // unfinished tasks and the most recent task are kept
+        assert_eq!(deleted, 2);
+
+        let remaining = store.list_tasks(None, None, None).await.unwrap();
+        let remaining: Vec<_> = remaining.iter().map(|task| task.id).collect();
+        assert_eq!(remaining, &[3, 1]);
+    }
+
+    #[actix_rt::test]
+    async fn test_delete_tasks_by_chunks() {
+        let tmp = tmp_env();
+        let update_dir = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(update_dir.path()).unwrap();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let mut tasks = Vec::new();
+        for id in 0..7 {
+            let index_uid = if id % 2 == 0 { "even" } else { "odd" };
+            let mut task = store
+                .register(
+                    IndexUid::new_unchecked(index_uid),
+                    TaskContent::IndexCreation { primary_key: None },
+                    TaskPriority::default(),
+                )
+                .await
+                .unwrap();
+            task.events.push(TaskEvent::Succeded {
+                result: TaskResult::Other,
+                timestamp: OffsetDateTime::now_utc(),
+            });
+            tasks.push(task);
+        }
+        store.update_tasks(tasks).await.unwrap();
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("odd".into());
+        let deleted = store
+            .delete_tasks_by_chunks(None, Some(filter), update_file_store.clone(), 2)
+            .await
+            .unwrap();
+        assert_eq!(deleted, 3);
+
+        // the most recent task is kept whatever the chunk it is in.
+        let deleted = store
+            .delete_tasks_by_chunks(None, None, update_file_store, 2)
+            .await
+            .unwrap();
+        assert_eq!(deleted, 3);
+
+        let remaining = store.list_tasks(None, None, None).await.unwrap();
+        let remaining: Vec<_> = remaining.iter().map(|task| task.id).collect();
+        assert_eq!(remaining, &[6]);
+    }
+
+    #[actix_rt::test]
+    async fn test_recover_interrupted_tasks() {
+        let tmp = tmp_env();
+        let update_dir = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(update_dir.path()).unwrap();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let contents = vec![
+            TaskContent::SettingsUpdate {
+                settings: Default::default(),
+                is_deletion: false,
+                allow_index_creation: true,
+            },
+            TaskContent::IndexCreation { primary_key: None },
+            TaskContent::IndexDeletion,
+            TaskContent::DocumentDeletion(DocumentDeletion::Clear),
+        ];
+        let mut tasks = Vec::new();
+        for content in contents {
+            let task = store
+                .register(
+                    IndexUid::new_unchecked("test"),
+                    content,
+                    TaskPriority::default(),
+                )
+                .await
+                .unwrap();
+            tasks.push(task);
+        }
+
+        // The first task succeeded, the next two were being processed when the instance crashed,
+        // and the last one was still enqueued.
+        for task in tasks.iter_mut().take(3) {
+            task.events.push(TaskEvent::Batched {
+                timestamp: OffsetDateTime::now_utc(),
+                batch_id: 0,
+            });
+            task.events
+                .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
+        }
+        tasks[0].events.push(TaskEvent::Succeded {
+            result: TaskResult::Other,
+            timestamp: OffsetDateTime::now_utc(),
+        });
+        tasks[1].content = TaskContent::DocumentDeletion(DocumentDeletion::Clear);
+        store.update_tasks(tasks).await.unwrap();
+
+        let recovered = store.recover_interrupted_tasks(&update_file_store).unwrap();
+        let recovered: Vec<_> = recovered.iter().map(|task| task.id).collect();
+        assert_eq!(recovered, &[1, 2]);
+
+        // The document deletion is enqueued again.
+        let task = store.get_task(1, None).await.unwrap();
+        assert!(matches!(task.events.as_slice(), [TaskEvent::Created(_)]));
+
+        // The index deletion may have been applied, it is marked as failed.
+        let task = store.get_task(2, None).await.unwrap();
+        match task.events.last() {
+            Some(TaskEvent::Failed { error, .. }) => {
+                assert_eq!(error, &ResponseError::from(TaskError::Interrupted(2)))
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+
+        // The other tasks are left untouched.
+        assert!(store.get_task(0, None).await.unwrap().is_finished());
+        let task = store.get_task(3, None).await.unwrap();
+        assert!(matches!(task.events.as_slice(), [TaskEvent::Created(_)]));
+
+        // Recovering again is a no-op.
+        assert!(store
+            .recover_interrupted_tasks(&update_file_store)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_recover_interrupted_addition_without_update_file() {
+        let tmp = tmp_env();
+        let update_dir = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(update_dir.path()).unwrap();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let (kept_uuid, update_file) = update_file_store.new_update().unwrap();
+        update_file.persist().unwrap();
+        let addition = |content_uuid| TaskContent::DocumentAddition {
+            content_uuid,
+            merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+            primary_key: None,
+            documents_count: 1,
+            allow_index_creation: true,
+            deep_merge: false,
+        };
+
+        let mut tasks = Vec::new();
+        for content in [addition(kept_uuid), addition(Uuid::new_v4())] {
+            let mut task = store
+                .register(
+                    IndexUid::new_unchecked("test"),
+                    content,
+                    TaskPriority::default(),
+                )
+                .await
+                .unwrap();
+            task.events
+                .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
+            tasks.push(task);
+        }
+        store.update_tasks(tasks).await.unwrap();
+
+        store.recover_interrupted_tasks(&update_file_store).unwrap();
+
+        // The addition whose update file is still there is enqueued again.
+        let task = store.get_task(0, None).await.unwrap();
+        assert!(matches!(task.events.as_slice(), [TaskEvent::Created(_)]));
+
+        // The update file of the other one was already deleted, it can't be processed again.
+        let task = store.get_task(1, None).await.unwrap();
+        match task.events.last() {
+            Some(TaskEvent::Failed { error, .. }) => {
+                assert_eq!(error, &ResponseError::from(TaskError::Interrupted(1)))
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_recover_interrupted_edition() {
+        let tmp = tmp_env();
+        let update_dir = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(update_dir.path()).unwrap();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let content = TaskContent::DocumentEdition {
+            filter: None,
+            edits: vec![DocumentEdit::Increment {
+                path: "views".to_string(),
+                by: 1.into(),
+            }],
+        };
+        let mut task = store
+            .register(
+                IndexUid::new_unchecked("test"),
+                content,
+                TaskPriority::default(),
+            )
+            .await
+            .unwrap();
+        task.events
+            .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
+        store.update_tasks(vec![task]).await.unwrap();
+
+        store.recover_interrupted_tasks(&update_file_store).unwrap();
+
+        // The edits may have been applied to some documents, applying them again would increment
+        // these twice.
+        let task = store.get_task(0, None).await.unwrap();
+        match task.events.last() {
+            Some(TaskEvent::Failed { error, .. }) => {
+                assert_eq!(error, &ResponseError::from(TaskError::Interrupted(0)))
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
 }
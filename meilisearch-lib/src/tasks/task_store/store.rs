@@ -5,14 +5,14 @@ const UID_TASK_IDS: &str = "uid_task_id";
 const TASKS: &str = "tasks";
 
 use std::borrow::Cow;
-use std::collections::BinaryHeap;
+use std::collections::HashSet;
 use std::convert::TryInto;
+use std::iter::Peekable;
 use std::mem::size_of;
-use std::ops::Range;
 use std::result::Result as StdResult;
 use std::sync::Arc;
 
-use milli::heed::types::{ByteSlice, OwnedType, SerdeJson, Unit};
+use milli::heed::types::{OwnedType, SerdeJson, Unit};
 use milli::heed::{BytesDecode, BytesEncode, Database, Env, RoTxn, RwTxn};
 
 use crate::tasks::task::{Task, TaskId};
@@ -114,112 +114,177 @@ impl Store {
         Ok(())
     }
 
+    /// Removes `task` from the store, along with its index entry.
+    pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        self.tasks.delete(txn, &BEU64::new(task.id))?;
+        self.uids_task_ids
+            .delete(txn, &(&task.index_uid, task.id))?;
+
+        Ok(())
+    }
+
     pub fn get(&self, txn: &RoTxn, id: TaskId) -> Result<Option<Task>> {
         let task = self.tasks.get(txn, &BEU64::new(id))?;
         Ok(task)
     }
 
-    pub fn list_tasks<'a>(
+    /// Returns the unfinished tasks starting from the task id `from`, in increasing id order.
+    pub fn fetch_unfinished_tasks(&self, txn: &RoTxn, from: Option<TaskId>) -> Result<Vec<Task>> {
+        let from = from.unwrap_or_default();
+
+        let result: StdResult<Vec<_>, milli::heed::Error> = self
+            .tasks
+            .range(txn, &(BEU64::new(from)..))?
+            .map(|r| r.map(|(_, t)| t))
+            .filter(|result| result.as_ref().map_or(true, |t| !t.is_finished()))
+            .collect();
+
+        result.map_err(Into::into)
+    }
+
+    /// Returns at most `limit` tasks matching `filter`, starting from the task id `from` and going
+    /// down, in decreasing id order. If `from` is `None`, the tasks are listed from the most recent
+    /// one.
+    pub fn list_tasks(
         &self,
-        txn: &'a RoTxn,
+        txn: &RoTxn,
         from: Option<TaskId>,
         filter: Option<TaskFilter>,
         limit: Option<usize>,
     ) -> Result<Vec<Task>> {
-        let from = from.unwrap_or_default();
-        let range = from..limit
-            .map(|limit| (limit as u64).saturating_add(from))
-            .unwrap_or(u64::MAX);
-        let iter: Box<dyn Iterator<Item = StdResult<_, milli::heed::Error>>> = match filter {
-            Some(
-                ref filter @ TaskFilter {
-                    indexes: Some(_), ..
-                },
-            ) => {
-                let iter = self
-                    .compute_candidates(txn, filter, range)?
-                    .into_iter()
-                    .filter_map(|id| self.tasks.get(txn, &BEU64::new(id)).transpose());
-
-                Box::new(iter)
-            }
-            _ => Box::new(
-                self.tasks
-                    .rev_range(txn, &(BEU64::new(range.start)..BEU64::new(range.end)))?
-                    .map(|r| r.map(|(_, t)| t)),
-            ),
+        let from = match from {
+            Some(from) => from,
+            None => self
+                .tasks
+                .lazily_decode_data()
+                .last(txn)?
+                .map_or(0, |(id, _)| id.get()),
         };
 
-        let apply_fitler = |task: &StdResult<_, milli::heed::Error>| match task {
-            Ok(ref t) => filter
+        let filter_fn = |task: &Task| {
+            filter
                 .as_ref()
-                .and_then(|filter| filter.filter_fn.as_ref())
-                .map(|f| f(t))
-                .unwrap_or(true),
-            Err(_) => true,
+                .and_then(|f| f.filter_fn.as_ref())
+                .map_or(true, |f| f(task))
         };
-        // Collect 'limit' task if it exists or all of them.
-        let tasks = iter
-            .filter(apply_fitler)
-            .take(limit.unwrap_or(usize::MAX))
-            .try_fold::<_, _, StdResult<_, milli::heed::Error>>(Vec::new(), |mut v, task| {
-                v.push(task?);
-                Ok(v)
-            })?;
-
-        Ok(tasks)
+
+        let result: StdResult<Vec<_>, milli::heed::Error> =
+            match filter.as_ref().and_then(|f| f.indexes.as_ref()) {
+                Some(indexes) => self
+                    .compute_candidates(txn, indexes, from)?
+                    .filter_map(|id| {
+                        id.and_then(|id| self.tasks.get(txn, &BEU64::new(id)))
+                            .transpose()
+                    })
+                    .filter(|result| result.as_ref().map_or(true, filter_fn))
+                    // Collect 'limit' task if it exists or all of them.
+                    .take(limit.unwrap_or(usize::MAX))
+                    .collect(),
+                None => self
+                    .tasks
+                    .rev_range(txn, &(..=BEU64::new(from)))?
+                    .map(|r| r.map(|(_, t)| t))
+                    .filter(|result| result.as_ref().map_or(true, filter_fn))
+                    // Collect 'limit' task if it exists or all of them.
+                    .take(limit.unwrap_or(usize::MAX))
+                    .collect(),
+            };
+
+        result.map_err(Into::into)
     }
 
-    fn compute_candidates(
+    /// Returns at most `limit` ids of the tasks lower or equal to `from`, in decreasing order,
+    /// restricted to the indexes of `filter` if it has any. The tasks are not deserialized.
+    pub fn task_ids(
         &self,
-        txn: &milli::heed::RoTxn,
-        filter: &TaskFilter,
-        range: Range<TaskId>,
-    ) -> Result<BinaryHeap<TaskId>> {
-        let mut candidates = BinaryHeap::new();
-        if let Some(ref indexes) = filter.indexes {
-            for index in indexes {
-                // We need to prefix search the null terminated string to make sure that we only
-                // get exact matches for the index, and not other uids that would share the same
-                // prefix, i.e test and test1.
-                let mut index_uid = index.as_bytes().to_vec();
-                index_uid.push(0);
-
-                self.uids_task_ids
-                    .remap_key_type::<ByteSlice>()
-                    .rev_prefix_iter(txn, &index_uid)?
-                    .map(|entry| -> StdResult<_, milli::heed::Error> {
-                        let (key, _) = entry?;
-                        let (_, id) = IndexUidTaskIdCodec::bytes_decode(key)
-                            .ok_or(milli::heed::Error::Decoding)?;
-                        Ok(id)
-                    })
-                    .skip_while(|entry| {
-                        entry
-                            .as_ref()
-                            .ok()
-                            // we skip all elements till we enter in the range
-                            .map(|key| !range.contains(key))
-                            // if we encounter an error we returns true to collect it later
-                            .unwrap_or(true)
-                    })
-                    .take_while(|entry| {
-                        entry
-                            .as_ref()
-                            .ok()
-                            // as soon as we are out of the range we exit
-                            .map(|key| range.contains(key))
-                            // if we encounter an error we returns true to collect it later
-                            .unwrap_or(true)
-                    })
-                    .try_for_each::<_, StdResult<(), milli::heed::Error>>(|id| {
-                        candidates.push(id?);
-                        Ok(())
-                    })?;
+        txn: &RoTxn,
+        from: Option<TaskId>,
+        filter: Option<&TaskFilter>,
+        limit: usize,
+    ) -> Result<Vec<TaskId>> {
+        let from = match from {
+            Some(from) => from,
+            None => self
+                .tasks
+                .lazily_decode_data()
+                .last(txn)?
+                .map_or(0, |(id, _)| id.get()),
+        };
+
+        let result: StdResult<Vec<_>, milli::heed::Error> =
+            match filter.and_then(|f| f.indexes.as_ref()) {
+                Some(indexes) => self
+                    .compute_candidates(txn, indexes, from)?
+                    .take(limit)
+                    .collect(),
+                None => self
+                    .tasks
+                    .lazily_decode_data()
+                    .rev_range(txn, &(..=BEU64::new(from)))?
+                    .map(|r| r.map(|(id, _)| id.get()))
+                    .take(limit)
+                    .collect(),
+            };
+
+        result.map_err(Into::into)
+    }
+
+    /// Returns the ids of the tasks of `indexes` that are lower or equal to `from`, in decreasing
+    /// order. The ids of the different indexes are merged lazily, so only the ids up to the last
+    /// one consumed are read.
+    fn compute_candidates<'txn>(
+        &self,
+        txn: &'txn RoTxn,
+        indexes: &HashSet<String>,
+        from: TaskId,
+    ) -> Result<impl Iterator<Item = StdResult<TaskId, milli::heed::Error>> + 'txn> {
+        let mut iters = Vec::with_capacity(indexes.len());
+        for index in indexes {
+            // The keys are null terminated, so the range only contains the tasks of this exact
+            // index, and not the ones of other uids sharing the same prefix, i.e test and test1.
+            let range = (index.as_str(), 0)..=(index.as_str(), from);
+            let iter = self
+                .uids_task_ids
+                .rev_range(txn, &range)?
+                .map(|entry| entry.map(|((_, id), ())| id));
+            iters.push(iter.peekable());
+        }
+
+        Ok(MergeDescending { iters })
+    }
+}
+
+/// Merges iterators of ids sorted in decreasing order into a single iterator, sorted in decreasing
+/// order too.
+struct MergeDescending<I: Iterator> {
+    iters: Vec<Peekable<I>>,
+}
+
+impl<I> Iterator for MergeDescending<I>
+where
+    I: Iterator<Item = StdResult<TaskId, milli::heed::Error>>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut max: Option<(usize, TaskId)> = None;
+        for (i, iter) in self.iters.iter_mut().enumerate() {
+            match iter.peek() {
+                Some(Ok(id)) => {
+                    if max.map_or(true, |(_, max)| *id > max) {
+                        max = Some((i, *id));
+                    }
+                }
+                // An error is returned as soon as it is encountered.
+                Some(Err(_)) => {
+                    max = Some((i, TaskId::MAX));
+                    break;
+                }
+                None => (),
             }
         }
 
-        Ok(candidates)
+        max.and_then(|(i, _)| self.iters[i].next())
     }
 }
 
@@ -296,6 +361,13 @@ pub mod test {
             }
         }
 
+        pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.delete(txn, task),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
         pub fn get(&self, txn: &RoTxn, id: TaskId) -> Result<Option<Task>> {
             match self {
                 MockStore::Real(index) => index.get(txn, id),
@@ -303,9 +375,33 @@ pub mod test {
             }
         }
 
-        pub fn list_tasks<'a>(
+        pub fn fetch_unfinished_tasks(
+            &self,
+            txn: &RoTxn,
+            from: Option<TaskId>,
+        ) -> Result<Vec<Task>> {
+            match self {
+                MockStore::Real(index) => index.fetch_unfinished_tasks(txn, from),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn task_ids(
+            &self,
+            txn: &RoTxn,
+            from: Option<TaskId>,
+            filter: Option<&TaskFilter>,
+            limit: usize,
+        ) -> Result<Vec<TaskId>> {
+            match self {
+                MockStore::Real(index) => index.task_ids(txn, from, filter, limit),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn list_tasks(
             &self,
-            txn: &'a RoTxn,
+            txn: &RoTxn,
             from: Option<TaskId>,
             filter: Option<TaskFilter>,
             limit: Option<usize>,
@@ -328,6 +424,7 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test"),
                 content: TaskContent::IndexDeletion,
                 events: vec![],
+                priority: Default::default(),
             })
             .collect::<Vec<_>>();
 
@@ -359,6 +456,7 @@ pub mod test {
             index_uid: IndexUid::new_unchecked("test"),
             content: TaskContent::IndexDeletion,
             events: vec![],
+            priority: Default::default(),
         };
 
         let task_2 = Task {
@@ -366,6 +464,7 @@ pub mod test {
             index_uid: IndexUid::new_unchecked("test1"),
             content: TaskContent::IndexDeletion,
             events: vec![],
+            priority: Default::default(),
         };
 
         let mut txn = store.wtxn().unwrap();
@@ -387,12 +486,14 @@ pub mod test {
             index_uid: IndexUid::new_unchecked("test"),
             content: TaskContent::IndexDeletion,
             events: vec![],
+            priority: Default::default(),
         };
         let task_2 = Task {
             id: 1,
             index_uid: IndexUid::new_unchecked("test1"),
             content: TaskContent::IndexDeletion,
             events: vec![],
+            priority: Default::default(),
         };
 
         let mut txn = store.wtxn().unwrap();
@@ -408,6 +509,89 @@ pub mod test {
         assert_eq!(&*tasks.first().unwrap().index_uid, "test");
     }
 
+    #[test]
+    fn test_list_tasks_from_and_limit() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let mut txn = store.wtxn().unwrap();
+        for id in 0..10 {
+            let index_uid = if id % 2 == 0 { "even" } else { "odd" };
+            let task = Task {
+                id,
+                index_uid: IndexUid::new_unchecked(index_uid),
+                content: TaskContent::IndexDeletion,
+                events: vec![],
+                priority: Default::default(),
+            };
+            store.put(&mut txn, &task).unwrap();
+        }
+
+        let ids = |tasks: Vec<Task>| tasks.into_iter().map(|t| t.id).collect::<Vec<_>>();
+
+        let tasks = store.list_tasks(&txn, None, None, Some(3)).unwrap();
+        assert_eq!(ids(tasks), &[9, 8, 7]);
+
+        let tasks = store.list_tasks(&txn, Some(4), None, Some(3)).unwrap();
+        assert_eq!(ids(tasks), &[4, 3, 2]);
+
+        // the candidates of several indexes must be merged in decreasing order.
+        let mut filter = TaskFilter::default();
+        filter.filter_index("even".into());
+        filter.filter_index("odd".into());
+        let tasks = store.list_tasks(&txn, Some(6), Some(filter), None).unwrap();
+        assert_eq!(ids(tasks), &[6, 5, 4, 3, 2, 1, 0]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("even".into());
+        filter.filter_index("odd".into());
+        let tasks = store.list_tasks(&txn, None, Some(filter), Some(3)).unwrap();
+        assert_eq!(ids(tasks), &[9, 8, 7]);
+
+        // the limit is applied after the filter.
+        let mut filter = TaskFilter::default();
+        filter.filter_index("odd".into());
+        filter.filter_fn(|task| task.id < 6);
+        let tasks = store.list_tasks(&txn, None, Some(filter), Some(2)).unwrap();
+        assert_eq!(ids(tasks), &[5, 3]);
+
+        let task_ids = store.task_ids(&txn, Some(8), None, 3).unwrap();
+        assert_eq!(task_ids, &[8, 7, 6]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("odd".into());
+        let task_ids = store.task_ids(&txn, Some(8), Some(&filter), 10).unwrap();
+        assert_eq!(task_ids, &[7, 5, 3, 1]);
+
+        let unfinished = store.fetch_unfinished_tasks(&txn, Some(7)).unwrap();
+        assert_eq!(ids(unfinished), &[7, 8, 9]);
+    }
+
+    #[test]
+    fn test_delete_task() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let task = Task {
+            id: 0,
+            index_uid: IndexUid::new_unchecked("test"),
+            content: TaskContent::IndexDeletion,
+            events: vec![],
+            priority: Default::default(),
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        store.put(&mut txn, &task).unwrap();
+        store.delete(&mut txn, &task).unwrap();
+
+        assert!(store.get(&txn, 0).unwrap().is_none());
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert!(tasks.is_empty());
+    }
+
     proptest! {
         #[test]
         fn encode_decode_roundtrip(index_uid in any::<IndexUid>(), task_id in 0..TaskId::MAX) {
@@ -12,7 +12,8 @@ use super::{Scheduler, TaskPerformer};
 use crate::tasks::task::TaskEvent;
 
 /// The update loop sequentially performs batches of updates by asking the scheduler for a batch,
-/// and handing it to the `TaskPerformer`.
+/// and handing it to the `TaskPerformer`. Several update loops can run concurrently, the scheduler
+/// never hands out two batches of the same index at once.
 pub struct UpdateLoop<P: TaskPerformer> {
     scheduler: Arc<RwLock<Scheduler>>,
     performer: Arc<P>,
@@ -67,12 +68,20 @@ where
                         .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
                 }
 
-                batch.tasks = {
-                    self.scheduler
-                        .read()
-                        .await
-                        .update_tasks(batch.tasks)
-                        .await?
+                let tasks = self
+                    .scheduler
+                    .read()
+                    .await
+                    .update_tasks(batch.tasks.clone())
+                    .await;
+                batch.tasks = match tasks {
+                    Ok(tasks) => tasks,
+                    Err(e) => {
+                        // The batch won't be processed, its tasks were already taken out of the
+                        // queue, they must be put back to be processed later.
+                        self.scheduler.write().await.requeue(batch.id, &batch.tasks);
+                        return Err(e);
+                    }
                 };
 
                 let performer = self.performer.clone();
@@ -84,6 +93,7 @@ where
             Pending::Job(job) => {
                 let performer = self.performer.clone();
                 performer.process_job(job).await;
+                self.scheduler.write().await.finish_job();
             }
             Pending::Nothing => (),
         }
@@ -95,12 +105,14 @@ where
     ///
     /// When a task is processed, the result of the process is pushed to its event list. The
     /// `handle_batch_result` make sure that the new state is saved to the store.
-    /// The tasks are then removed from the processing queue.
+    /// The batch is then removed from the processing batches, even if the new state couldn't be
+    /// saved, so that its index can be scheduled again.
     async fn handle_batch_result(&self, mut batch: Batch) -> Result<()> {
         let mut scheduler = self.scheduler.write().await;
-        let tasks = scheduler.update_tasks(batch.tasks).await?;
-        scheduler.finish();
+        let tasks = scheduler.update_tasks(batch.tasks).await;
+        scheduler.finish(batch.id);
         drop(scheduler);
+        let tasks = tasks?;
         batch.tasks = tasks;
         self.performer.finish(&batch).await;
         Ok(())
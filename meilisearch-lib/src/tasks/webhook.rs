@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use log::{error, warn};
+use reqwest::header::AUTHORIZATION;
+use serde_json::Value;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::time::sleep;
+
+use super::task::Task;
+
+/// The number of finished tasks that can wait to be sent before new ones are dropped.
+const OUTBOX_CAPACITY: usize = 1024;
+/// The number of times the delivery of a notification is retried before it is dropped.
+const MAX_RETRIES: u32 = 5;
+/// The delay before the first retry, it is doubled after each failed attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// The maximum duration of a single notification request, connection included.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// The maximum duration to establish the connection to the webhook.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Builds the body of the notification sent for a finished task.
+pub type WebhookPayload = fn(Task) -> Value;
+
+/// Notifies a webhook of the finished tasks.
+///
+/// The tasks are queued in a bounded outbox, and posted in order by a background task.
+#[derive(Clone)]
+pub struct TaskWebhook {
+    outbox: mpsc::Sender<Task>,
+}
+
+impl TaskWebhook {
+    /// Spawns the background task posting the notifications to `url`.
+    pub fn spawn(
+        url: String,
+        authorization: Option<String>,
+        payload: WebhookPayload,
+    ) -> reqwest::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()?;
+
+        let (outbox, receiver) = mpsc::channel(OUTBOX_CAPACITY);
+        let sender = WebhookSender {
+            client,
+            url,
+            authorization,
+            payload,
+        };
+
+        tokio::task::spawn_local(sender.run(receiver));
+
+        Ok(Self { outbox })
+    }
+
+    /// Queues a notification for `task`. It is dropped if the outbox is full.
+    pub fn notify(&self, task: Task) {
+        match self.outbox.try_send(task) {
+            Ok(()) => (),
+            Err(TrySendError::Full(task)) => {
+                warn!(
+                    "Webhook outbox is full, dropping notification for task {}.",
+                    task.id
+                )
+            }
+            Err(TrySendError::Closed(task)) => {
+                error!(
+                    "Webhook is closed, dropping notification for task {}.",
+                    task.id
+                )
+            }
+        }
+    }
+}
+
+struct WebhookSender {
+    client: reqwest::Client,
+    url: String,
+    authorization: Option<String>,
+    payload: WebhookPayload,
+}
+
+impl WebhookSender {
+    async fn run(self, mut outbox: mpsc::Receiver<Task>) {
+        while let Some(task) = outbox.recv().await {
+            let id = task.id;
+            let payload = (self.payload)(task);
+            if let Err(e) = self.send(&payload).await {
+                error!("Could not notify the webhook for task {}: {}", id, e);
+            }
+        }
+    }
+
+    /// Posts `payload` to the webhook, retrying with an exponential backoff when the webhook
+    /// can't be reached or fails with a server error. A client error is not retried, since the
+    /// same request would be rejected again.
+    async fn send(&self, payload: &Value) -> reqwest::Result<()> {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut retries = 0;
+
+        loop {
+            match self.post(payload).await {
+                Ok(()) => return Ok(()),
+                Err(e) if retries < MAX_RETRIES && is_retryable(&e) => {
+                    warn!(
+                        "Webhook notification failed, retrying in {:?}: {}",
+                        backoff, e
+                    );
+                    sleep(backoff).await;
+                    backoff *= 2;
+                    retries += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn post(&self, payload: &Value) -> reqwest::Result<()> {
+        let mut request = self.client.post(&self.url).json(payload);
+        if let Some(ref authorization) = self.authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+
+        request.send().await?.error_for_status()?;
+
+        Ok(())
+    }
+}
+
+/// Returns whether a failed notification can be sent again: the transport errors and the server
+/// errors are transient, unlike the client errors.
+fn is_retryable(error: &reqwest::Error) -> bool {
+    error
+        .status()
+        .map_or(true, |status| status.is_server_error())
+}
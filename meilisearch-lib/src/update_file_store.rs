@@ -176,11 +176,25 @@ mod store {
             Ok(self.get_update(uuid)?.metadata()?.len())
         }
 
+        /// Returns whether the update file pointed to by `uuid` exists.
+        pub fn exists(&self, uuid: Uuid) -> bool {
+            self.path.join(uuid.to_string()).exists()
+        }
+
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
             let path = self.path.join(uuid.to_string());
             tokio::fs::remove_file(path).await?;
             Ok(())
         }
+
+        /// Deletes the update file pointed to by `uuid`, ignoring it if it was already deleted.
+        pub async fn delete_if_exists(&self, uuid: Uuid) -> Result<()> {
+            let path = self.path.join(uuid.to_string());
+            match tokio::fs::remove_file(path).await {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                result => Ok(result?),
+            }
+        }
     }
 }
 
@@ -246,11 +260,25 @@ mod test {
             }
         }
 
+        pub fn exists(&self, uuid: Uuid) -> bool {
+            match self {
+                MockUpdateFileStore::Real(s) => s.exists(uuid),
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
+
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
             match self {
                 MockUpdateFileStore::Real(s) => s.delete(uuid).await,
                 MockUpdateFileStore::Mock(_) => todo!(),
             }
         }
+
+        pub async fn delete_if_exists(&self, uuid: Uuid) -> Result<()> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.delete_if_exists(uuid).await,
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
     }
 }